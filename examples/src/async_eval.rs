@@ -0,0 +1,57 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Wake;
+use std::thread::Thread;
+
+/*
+  The async API does not depend on a specific runtime so it works with
+  tokio, async-std, smol, etc. This example uses a tiny executor that
+  parks the current thread until the future is woken.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+  let worker = nodejs.spawn_worker_thread()?;
+
+  let result = block_on(async {
+    // Evaluate on the main thread
+    let pid: u32 = nodejs.eval_async("process.pid").await?;
+    println!("main: {}", pid);
+
+    // Evaluate on a worker thread
+    let thread_id: u32 = worker
+      .eval_async("require('node:worker_threads').threadId")
+      .await?;
+    println!("worker: {}", thread_id);
+
+    nodejs.import_async("node:fs").await?;
+
+    Ok(())
+  });
+
+  edon::Nodejs::shutdown()?;
+  result
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+  struct ThreadWaker(Thread);
+
+  impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+      self.0.unpark();
+    }
+  }
+
+  let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+  let mut cx = Context::from_waker(&waker);
+  let mut future = pin!(future);
+
+  loop {
+    match future.as_mut().poll(&mut cx) {
+      Poll::Ready(output) => return output,
+      Poll::Pending => std::thread::park(),
+    }
+  }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use edon::EvalOptions;
+
+/*
+  The purpose of this example is to demonstrate exporting async
+  Rust functions that return a Promise to JavaScript.
+
+  The futures run on a tokio runtime and the calls can be
+  cancelled from JavaScript with an AbortSignal.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let runtime = tokio::runtime::Runtime::new()?;
+  let handle = runtime.handle().clone();
+
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  nodejs.napi_module_register("example_async", move |env, mut exports| {
+    let handle = handle.clone();
+
+    // The first AbortSignal argument cancels the call, the future
+    // is dropped and the Promise rejects with the abort reason
+    let sleep = env.create_async_function(
+      "sleep",
+      move |future| {
+        handle.spawn(future);
+      },
+      |ms: u32| async move {
+        tokio::time::sleep(Duration::from_millis(ms.into())).await;
+        Ok(format!("Slept for {}ms", ms))
+      },
+    )?;
+    exports.set_named_property("sleep", sleep)?;
+
+    Ok(exports)
+  })?;
+
+  let worker = nodejs.spawn_worker_thread()?;
+
+  worker.eval_with_options_blocking::<()>(
+    r#"
+    (async () => {
+      const { sleep } = require('native:example_async')
+      console.log(await sleep(100)) // "Slept for 100ms"
+
+      const controller = new AbortController()
+      setTimeout(() => controller.abort(), 100)
+      try {
+        await sleep(60_000, controller.signal)
+      } catch (error) {
+        console.log(error.name) // "AbortError"
+      }
+    })()
+  "#,
+    &EvalOptions {
+      await_promise: true,
+      ..Default::default()
+    },
+  )?;
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
@@ -0,0 +1,46 @@
+use edon::Channel;
+
+/*
+  The purpose of this example is to demonstrate streaming messages
+  in both directions between Rust and a JavaScript context.
+
+  The JavaScript end of the channel is an EventEmitter assigned
+  to globalThis.plugin. Rust sends Strings to JavaScript and
+  JavaScript posts numbers back to Rust.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+  let worker = nodejs.spawn_worker_thread()?;
+
+  // Rust receives u32 values and sends String values
+  let channel: Channel<u32, String> = worker.open_channel("plugin")?;
+
+  worker.eval_blocking::<()>(
+    r#"
+      plugin.on("message", (word) => {
+        if (word === "done") {
+          plugin.close();
+        } else {
+          plugin.postMessage(word.length);
+        }
+      });
+    "#,
+  )?;
+
+  for word in ["hello", "from", "rust"] {
+    channel.send(word.to_string())?;
+  }
+  channel.send("done".to_string())?;
+
+  // Receive until JavaScript closes the channel
+  let mut lengths = vec![];
+  while let Ok(length) = channel.recv() {
+    lengths.push(length);
+  }
+
+  println!("Word lengths: {:?}", lengths);
+  assert!(lengths == vec![5, 4, 4]);
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
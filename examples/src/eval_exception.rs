@@ -0,0 +1,32 @@
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  // Exceptions thrown by evaluated code are returned as Rust errors
+  let result = nodejs.eval_blocking::<()>(
+    r#"
+    throw new TypeError("Something went wrong", { cause: new Error("Root cause") })
+  "#,
+  );
+
+  match result {
+    Err(edon::Error::JsException {
+      name,
+      message,
+      cause,
+      ..
+    }) => {
+      println!("{}: {}", name, message); // "TypeError: Something went wrong"
+      println!("{:?}", cause);
+    }
+    _ => anyhow::bail!("Expected a JavaScript exception"),
+  }
+
+  // The same applies to modules that fail to load, the error records which operation threw
+  let result = nodejs.require("./does-not-exist.js");
+  if let Err(edon::Error::JsException { operation, .. }) = result {
+    println!("{:?}", operation); // "Some(Require)"
+  }
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
@@ -2,10 +2,15 @@ pub fn main() -> anyhow::Result<()> {
   let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
 
   // Evaluate CJS script to set a global variable
-  nodejs.eval_blocking("globalThis.meaningOfLife = 42;")?;
+  nodejs.eval_blocking::<u32>("globalThis.meaningOfLife = 42;")?;
 
   // Evaluate CJS script that inspects the global variable
-  nodejs.eval_blocking("console.log(globalThis.meaningOfLife)")?;
+  nodejs.eval_blocking::<()>("console.log(globalThis.meaningOfLife)")?;
 
+  // Evaluate CJS script and read the result back into Rust
+  let meaning_of_life: u32 = nodejs.eval_blocking("globalThis.meaningOfLife")?;
+  println!("{}", meaning_of_life);
+
+  edon::Nodejs::shutdown()?;
   Ok(())
 }
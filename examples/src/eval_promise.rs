@@ -0,0 +1,43 @@
+use edon::napi::JsPromise;
+use edon::EvalOptions;
+
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  // Wait for the Promise returned by the script to settle
+  // and read the resolved value back into Rust
+  let message: String = nodejs.eval_with_options_blocking(
+    r#"
+    new Promise(resolve => setTimeout(() => resolve("Hello World"), 100))
+  "#,
+    &EvalOptions {
+      await_promise: true,
+      ..Default::default()
+    },
+  )?;
+
+  println!("{}", message);
+
+  // Attach continuations to a Promise from native code
+  nodejs.exec_blocking(|env| {
+    let promise: JsPromise = env.run_script("Promise.resolve(42)")?;
+
+    promise
+      .then(|_env, value| {
+        println!("Resolved: {}", value.coerce_to_number()?.get_uint32()?);
+        Ok(())
+      })?
+      .catch(|_env, error| {
+        println!(
+          "Rejected: {}",
+          error.coerce_to_string()?.into_utf8()?.as_str()?
+        );
+        Ok(())
+      })?;
+
+    Ok(())
+  })?;
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
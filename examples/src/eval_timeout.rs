@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+/*
+  The purpose of this example is to demonstrate interrupting
+  a runaway script with a timeout.
+
+  The worker remains usable after the script is interrupted.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+  let worker = nodejs.spawn_worker_thread()?;
+
+  let result = worker.eval_with_timeout::<()>("while (true) {}", Duration::from_millis(100));
+
+  println!("Infinite loop: {:?}", result);
+  assert!(matches!(result, Err(edon::Error::Timeout)));
+
+  let value = worker.eval_with_timeout::<u32>("1 + 1", Duration::from_millis(100))?;
+  println!("After timeout: {}", value);
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
@@ -5,10 +5,11 @@ pub fn main() -> anyhow::Result<()> {
   let worker = nodejs.spawn_worker_thread()?;
 
   // Evaluate CJS script to set a global variable
-  worker.eval_blocking("globalThis.meaningOfLife = 42;")?;
+  worker.eval_blocking::<u32>("globalThis.meaningOfLife = 42;")?;
 
   // Evaluate CJS script that inspects the global variable
-  worker.eval_blocking("console.log(globalThis.meaningOfLife)")?;
+  worker.eval_blocking::<()>("console.log(globalThis.meaningOfLife)")?;
 
+  edon::Nodejs::shutdown()?;
   Ok(())
 }
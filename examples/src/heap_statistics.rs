@@ -0,0 +1,57 @@
+use std::thread;
+use std::time::Duration;
+
+use edon::NodejsOptions;
+
+/*
+  The purpose of this example is to demonstrate monitoring the
+  memory usage of Nodejs threads.
+
+  The worker has a capped heap and fills it up while a sampler
+  reports how close it is to the limit.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load(NodejsOptions {
+    libnode_path: edon::auto_resolve_libnode()?,
+    max_old_space_size: Some(512),
+    ..Default::default()
+  })?;
+
+  let stats = nodejs.heap_statistics()?;
+  println!("Main heap limit: {} bytes", stats.heap.heap_size_limit);
+  println!("Process rss: {} bytes", stats.memory.rss);
+
+  let worker = nodejs.spawn_worker_thread()?;
+
+  let sampler = worker.sample_heap_statistics(Duration::from_millis(100), |stats| {
+    let Ok(stats) = stats else {
+      return;
+    };
+    println!(
+      "Worker heap {:.1}%, event loop {:.1}%",
+      stats.heap.heap_usage() * 100.0,
+      stats.event_loop_utilization.utilization * 100.0,
+    );
+  });
+
+  worker.eval::<&str>(
+    "
+      globalThis.data = []
+      const fill = () => {
+        globalThis.data.push(new Array(100_000).fill('🍝'))
+        if (globalThis.data.length < 50) setTimeout(fill, 10)
+      }
+      fill()
+    ",
+    |_, _| {},
+  )?;
+
+  thread::sleep(Duration::from_secs(1));
+  drop(sampler);
+
+  let stats = worker.heap_statistics()?;
+  println!("Worker limits: {:?}", stats.resource_limits);
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
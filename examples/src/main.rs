@@ -1,13 +1,32 @@
 // Usage
 // cargo run --package edon_examples -- eval_main
 
+mod async_eval;
+mod async_function;
 mod basic;
+mod channel;
+mod eval_exception;
 mod eval_main;
+mod eval_promise;
+mod eval_timeout;
 mod eval_workers;
+mod heap_statistics;
+mod module_loader;
 mod multiple_contexts;
 mod multiple_contexts_load_balance;
 mod native_exec;
 mod native_module;
+mod native_module_builder;
+mod native_module_reload;
+mod shutdown;
+mod virtual_modules;
+mod vm_contexts;
+mod worker_cwd;
+mod worker_exit;
+mod worker_output;
+mod worker_pool;
+mod worker_resource_limits;
+mod worker_terminate;
 
 fn main() -> anyhow::Result<()> {
   let example = std::env::args()
@@ -17,13 +36,32 @@ fn main() -> anyhow::Result<()> {
     .unwrap_or("basic".to_string());
 
   match example.as_str() {
+    "async_eval" => async_eval::main(),
+    "async_function" => async_function::main(),
     "basic" => basic::main(),
+    "channel" => channel::main(),
+    "eval_exception" => eval_exception::main(),
     "eval_main" => eval_main::main(),
+    "eval_promise" => eval_promise::main(),
+    "eval_timeout" => eval_timeout::main(),
     "eval_workers" => eval_workers::main(),
+    "heap_statistics" => heap_statistics::main(),
+    "module_loader" => module_loader::main(),
     "multiple_contexts" => multiple_contexts::main(),
     "multiple_contexts_load_balance" => multiple_contexts_load_balance::main(),
     "native_exec" => native_exec::main(),
     "native_module" => native_module::main(),
+    "native_module_builder" => native_module_builder::main(),
+    "native_module_reload" => native_module_reload::main(),
+    "shutdown" => shutdown::main(),
+    "virtual_modules" => virtual_modules::main(),
+    "vm_contexts" => vm_contexts::main(),
+    "worker_cwd" => worker_cwd::main(),
+    "worker_exit" => worker_exit::main(),
+    "worker_output" => worker_output::main(),
+    "worker_pool" => worker_pool::main(),
+    "worker_resource_limits" => worker_resource_limits::main(),
+    "worker_terminate" => worker_terminate::main(),
     _ => Err(anyhow::anyhow!("No example for: \"{}\"", example)),
   }
 }
@@ -0,0 +1,59 @@
+use edon::LoadedModule;
+use edon::ModuleFormat;
+use edon::ModuleLoader;
+
+/*
+  The purpose of this example is to demonstrate resolving and
+  loading ES Modules from Rust.
+
+  Specifiers starting with "plugin:" are resolved to a custom
+  "edon:" scheme and their source is generated by Rust.
+*/
+struct PluginLoader;
+
+impl ModuleLoader for PluginLoader {
+  fn resolve(
+    &self,
+    specifier: &str,
+    _parent: Option<&str>,
+  ) -> edon::Result<Option<String>> {
+    Ok(
+      specifier
+        .strip_prefix("plugin:")
+        .map(|name| format!("edon:plugin/{}", name)),
+    )
+  }
+
+  fn load(
+    &self,
+    url: &str,
+  ) -> edon::Result<Option<LoadedModule>> {
+    let Some(name) = url.strip_prefix("edon:plugin/") else {
+      return Ok(None);
+    };
+
+    Ok(Some(LoadedModule {
+      format: ModuleFormat::Module,
+      source: format!("export const name = {:?};", name).into(),
+    }))
+  }
+}
+
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  nodejs.add_module_loader(PluginLoader);
+
+  let name = nodejs.eval_with_options_blocking::<String>(
+    "import('plugin:hello').then(m => m.name)",
+    &edon::EvalOptions {
+      await_promise: true,
+      ..Default::default()
+    },
+  )?;
+
+  println!("Loaded plugin: {}", name);
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
@@ -1,7 +1,3 @@
-use std::sync::mpsc::channel;
-
-use edon::napi::JsNumber;
-
 /*
   The purpose of this example is to demonstrate offloading Nodejs work
   across multiple Nodejs Worker thread contexts.
@@ -30,24 +26,15 @@ pub fn main() -> anyhow::Result<()> {
     // Spawn a Rust thread
     handles.push(std::thread::spawn(move || -> anyhow::Result<i32> {
       // Set the initial value in the JavaScript context
-      ctx.eval_blocking("globalThis.sum = 0;")?;
+      ctx.eval_blocking::<i32>("globalThis.sum = 0;")?;
 
       // Do addition in the JavaScript context using eval statements
       for _ in 0..add_until {
-        ctx.eval_blocking("globalThis.sum += 1;")?;
+        ctx.eval_blocking::<i32>("globalThis.sum += 1;")?;
       }
 
-      // Extract value stored inside JavaScript using native code
-      let (tx, rx) = channel();
-
-      ctx.exec_blocking(move |env| {
-        let global_this = env.get_global()?;
-        let i = global_this.get_named_property::<JsNumber>("sum")?;
-        tx.send(i.get_int32()?).unwrap();
-        Ok(())
-      })?;
-
-      Ok(rx.recv()?)
+      // Extract value stored inside JavaScript
+      Ok(ctx.eval_blocking("globalThis.sum")?)
     }));
   }
 
@@ -59,5 +46,6 @@ pub fn main() -> anyhow::Result<()> {
   println!("Summed total: {}", total);
   assert!(add_until * threads == total, "Invalid sum received");
 
+  edon::Nodejs::shutdown()?;
   Ok(())
 }
@@ -1,3 +1,5 @@
+use edon::napi::JsNumber;
+
 pub fn main() -> anyhow::Result<()> {
   let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
 
@@ -23,10 +25,19 @@ pub fn main() -> anyhow::Result<()> {
   })?;
 
   // Evaluate script that inspects the value set by the native code
-  worker.eval_blocking("console.log(globalThis.meaning)")?; // "42"
+  worker.eval_blocking::<()>("console.log(globalThis.meaning)")?; // "42"
+
+  // Values returned from native code are passed back to the caller
+  let meaning = worker.exec_blocking(|env| {
+    let global_this = env.get_global()?;
+    let meaning = global_this.get_named_property::<JsNumber>("meaning")?;
+    Ok(meaning.get_uint32()?)
+  })?;
+
+  println!("{}", meaning); // "42"
 
   // Evaluate script that demonstrates waiting for tasks to end before continuing
-  worker.eval_blocking(
+  worker.eval_blocking::<()>(
     r#"
     ;(async () => {
       console.log(globalThis.meaning)
@@ -34,7 +45,7 @@ pub fn main() -> anyhow::Result<()> {
   "#,
   )?;
 
-  worker.eval_blocking(
+  worker.eval_blocking::<()>(
     r#"
     ;(async () => {
       const fs = await import('node:fs')
@@ -45,5 +56,6 @@ pub fn main() -> anyhow::Result<()> {
   "#,
   )?;
 
+  edon::Nodejs::shutdown()?;
   Ok(())
 }
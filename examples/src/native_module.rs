@@ -10,6 +10,13 @@ pub fn main() -> anyhow::Result<()> {
     let value = env.create_uint32(42)?;
     exports.set_property(key, value)?;
 
+    // Export a function, its arguments are converted from JavaScript
+    // and invalid ones throw a TypeError
+    let repeat = env.create_typed_function("repeat", |text: String, times: Option<u32>| {
+      Ok(text.repeat(times.unwrap_or(1) as usize))
+    })?;
+    exports.set_named_property("repeat", repeat)?;
+
     // Export it from the module
     Ok(exports)
   })?;
@@ -18,12 +25,19 @@ pub fn main() -> anyhow::Result<()> {
   let ctx0 = nodejs.spawn_worker_thread()?;
 
   // Evaluate arbitrary code within the context
-  ctx0.eval_blocking(
+  ctx0.eval_blocking::<()>(
     r#"
-    const native = process._linkedBinding('example_native_module')
+    const native = require('native:example_native_module')
     console.log(native)
+    console.log(native.repeat('🍝', 3)) // "🍝🍝🍝"
+    try {
+      native.repeat('🍝', 'three')
+    } catch (error) {
+      console.log(error.message) // "Invalid argument 2 passed to repeat: ..."
+    }
   "#,
   )?;
 
+  edon::Nodejs::shutdown()?;
   Ok(())
 }
@@ -0,0 +1,57 @@
+use edon::EvalOptions;
+use edon::NativeModule;
+
+/*
+  The purpose of this example is to demonstrate declaring a
+  native module rather than filling in its exports by hand.
+
+  The module exports a constant, typed functions and a nested
+  namespace, which are created for every thread that loads it.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  let module = NativeModule::builder("example_builder")
+    .constant("version", "1.0.0")
+    .function("greet", |name: String| Ok(format!("Hello {}", name)))
+    .namespace("math", |math| {
+      math
+        .function("add", |a: f64, b: f64| Ok(a + b))
+        .function("sqrt", |x: f64| Ok(x.sqrt()))
+    })
+    .build()?;
+
+  nodejs.register_native_module(module)?;
+
+  let worker = nodejs.spawn_worker_thread()?;
+
+  worker.eval_blocking::<()>(
+    r#"
+    const native = require('native:example_builder')
+    console.log(native.version) // "1.0.0"
+    console.log(native.greet('World')) // "Hello World"
+    console.log(native.math.add(1, 2)) // 3
+    console.log(native.math.sqrt(16)) // 4
+  "#,
+  )?;
+
+  // Properties of the module are named exports when it is imported
+  let sum = worker.eval_with_options_blocking::<f64>(
+    "import('native:example_builder').then(({ math }) => math.add(2, 3))",
+    &EvalOptions {
+      await_promise: true,
+      ..Default::default()
+    },
+  )?;
+  println!("{}", sum); // 5
+
+  // Exporting a name twice is rejected when the module is built
+  let duplicate = NativeModule::builder("example_duplicate")
+    .constant("value", 1)
+    .constant("value", 2)
+    .build();
+  println!("{:?}", duplicate.err()); // "Native module exports the same name more than once: ..."
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
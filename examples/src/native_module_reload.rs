@@ -0,0 +1,42 @@
+use edon::EvalOptions;
+
+/*
+  The purpose of this example is to demonstrate registering
+  native modules while Nodejs is running and replacing them.
+
+  The module is registered after a worker has started, then replaced
+  with a new version which is picked up by the next require().
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+  let worker = nodejs.spawn_worker_thread()?;
+
+  nodejs.napi_module_register("example_plugin", |env, mut exports| {
+    exports.set_named_property("version", env.create_string("first")?)?;
+    Ok(exports)
+  })?;
+
+  let version = worker.eval_blocking::<String>("require('native:example_plugin').version")?;
+  println!("{}", version); // "first"
+
+  let replaced = nodejs.napi_module_replace("example_plugin", |env, mut exports| {
+    exports.set_named_property("version", env.create_string("second")?)?;
+    Ok(exports)
+  })?;
+  println!("Module version: {}", replaced); // 2
+
+  let version = worker.eval_blocking::<String>("require('native:example_plugin').version")?;
+  println!("{}", version); // "second"
+
+  let version = worker.eval_with_options_blocking::<String>(
+    "import('native:example_plugin').then(({ version }) => version)",
+    &EvalOptions {
+      await_promise: true,
+      ..Default::default()
+    },
+  )?;
+  println!("{}", version); // "second"
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
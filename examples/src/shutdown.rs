@@ -0,0 +1,35 @@
+/*
+  The purpose of this example is to demonstrate the lifecycle
+  of the Nodejs runtime.
+
+  Handles can be dropped and loaded again while Nodejs is running.
+  Once it is shut down, remaining handles return an error rather
+  than panicking and Nodejs cannot be loaded again.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let libnode_path = edon::auto_resolve_libnode()?;
+
+  {
+    let nodejs = edon::Nodejs::load_default(&libnode_path)?;
+    nodejs.eval_blocking::<()>("globalThis.counter = 1")?;
+  }
+
+  // Nodejs keeps running after every handle was dropped
+  let nodejs = edon::Nodejs::load_default(&libnode_path)?;
+  let counter: u32 = nodejs.eval_blocking("globalThis.counter")?;
+  println!("Counter: {}", counter); // "1"
+
+  assert!(edon::Nodejs::is_running());
+  edon::Nodejs::shutdown()?;
+  assert!(!edon::Nodejs::is_running());
+
+  let result = nodejs.eval_blocking::<u32>("globalThis.counter");
+  println!("After shutdown: {:?}", result); // "Err(NodejsNotRunning)"
+  assert!(matches!(result, Err(edon::Error::NodejsNotRunning)));
+
+  // Shutting down is terminal, Nodejs cannot be started again in this process
+  let result = edon::Nodejs::load_default(&libnode_path);
+  assert!(matches!(result, Err(edon::Error::NodejsShutDown)));
+
+  Ok(())
+}
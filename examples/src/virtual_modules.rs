@@ -0,0 +1,38 @@
+/*
+  The purpose of this example is to demonstrate loading JavaScript
+  embedded in the Rust binary without writing it to disk.
+
+  Virtual modules can be loaded with require() and import() and
+  can import each other with relative paths.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  let plugins = std::env::current_dir()?.join("virtual-plugins");
+
+  nodejs.add_virtual_module(
+    plugins.join("greet.cjs"),
+    "module.exports = (name) => `Hello ${name}`",
+  )?;
+
+  nodejs.add_virtual_module(plugins.join("config.json"), r#"{ "name": "World" }"#)?;
+
+  nodejs.add_virtual_module(
+    plugins.join("index.mjs"),
+    r#"
+      import greet from "./greet.cjs";
+      import config from "./config.json" with { type: "json" };
+      console.log(greet(config.name));
+    "#,
+  )?;
+
+  nodejs.import(plugins.join("index.mjs").to_str().unwrap())?;
+
+  nodejs.eval_blocking::<()>(format!(
+    "console.log(require({:?})('Rust'))",
+    plugins.join("greet.cjs")
+  ))?;
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
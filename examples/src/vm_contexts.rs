@@ -0,0 +1,39 @@
+/*
+  The purpose of this example is to demonstrate running many
+  isolated tenants within a single Nodejs worker thread.
+
+  Each context has its own globalThis so tenants can't see
+  each other's globals.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+  let worker = nodejs.spawn_worker_thread()?;
+
+  let mut contexts = vec![];
+  for i in 0..100 {
+    let ctx = worker.create_context()?;
+    ctx.eval_blocking::<()>(format!("globalThis.tenant = {}", i))?;
+    contexts.push(ctx);
+  }
+
+  for (i, ctx) in contexts.iter().enumerate() {
+    let tenant = ctx.eval_blocking::<u32>("globalThis.tenant")?;
+    assert!(tenant == i as u32);
+  }
+
+  // Globals are not shared with the worker
+  let leaked = worker.eval_blocking::<bool>("typeof globalThis.tenant !== 'undefined'")?;
+  assert!(!leaked);
+
+  // Native code gets the globalThis of the context
+  contexts[0].exec_blocking(|env, mut global| {
+    global.set_named_property("fromRust", env.create_string("Hello World")?)?;
+    Ok(())
+  })?;
+
+  let message = contexts[0].eval_blocking::<String>("fromRust")?;
+  println!("{}", message);
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::fs;
+
+use edon::WorkerEnv;
+use edon::WorkerOptions;
+
+/*
+  The purpose of this example is to demonstrate running workers
+  for different project roots in the same process.
+
+  Each worker has its own working directory, so the same relative
+  require() loads a different file, and its own environment.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  let mut workers = vec![];
+
+  for project in ["project-a", "project-b"] {
+    let root = std::env::temp_dir().join("edon-worker-cwd").join(project);
+    fs::create_dir_all(&root)?;
+    fs::write(
+      root.join("plugin.js"),
+      format!("module.exports = () => '{}'", project),
+    )?;
+
+    let worker = nodejs.spawn_worker_thread_with_options(&WorkerOptions {
+      cwd: Some(root),
+      env: WorkerEnv::Custom(HashMap::from([(
+        "PROJECT".to_string(),
+        project.to_string(),
+      )])),
+      ..Default::default()
+    })?;
+
+    workers.push(worker);
+  }
+
+  for worker in &workers {
+    let result = worker.eval_blocking::<String>(
+      "`${require('./plugin.js')()} in ${process.cwd()} (PROJECT=${process.env.PROJECT})`",
+    )?;
+    println!("{}", result);
+  }
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
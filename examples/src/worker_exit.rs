@@ -0,0 +1,37 @@
+use std::sync::mpsc::channel;
+
+use edon::WorkerStatus;
+
+/*
+  The purpose of this example is to demonstrate detecting
+  workers that die.
+
+  The worker throws an uncaught exception, the host is notified
+  through on_exit and later requests return Error::WorkerExited.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+  let worker = nodejs.spawn_worker_thread()?;
+
+  let (tx, rx) = channel();
+  worker.on_exit(move |exit| {
+    tx.send(exit).ok();
+  });
+
+  worker.eval::<&str>("setTimeout(() => { throw new Error('Oh no') })", |_, _| {})?;
+
+  let exit = rx.recv()?;
+  println!("Exit code: {}", exit.code); // "1"
+  if let Some(error) = exit.error {
+    println!("Uncaught: {}", error); // "Error: Oh no"
+  }
+
+  assert!(matches!(worker.status(), WorkerStatus::Exited(_)));
+
+  let result = worker.eval_blocking::<u32>("1 + 1");
+  println!("After exit: {:?}", result); // "Err(WorkerExited)"
+  assert!(matches!(result, Err(edon::Error::WorkerExited)));
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
@@ -0,0 +1,48 @@
+use edon::OutputBuffer;
+use edon::OutputSink;
+use edon::WorkerOptions;
+
+/*
+  The purpose of this example is to demonstrate capturing the
+  stdout and stderr of each worker rather than forwarding it to
+  the host process.
+
+  Each tenant gets its own worker, stdout is collected into a buffer
+  and stderr is passed to a callback that labels it with the tenant.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  let mut outputs = vec![];
+
+  for tenant in ["tenant-a", "tenant-b"] {
+    let stdout = OutputBuffer::default();
+
+    let worker = nodejs.spawn_worker_thread_with_options(&WorkerOptions {
+      stdout: OutputSink::Buffer(stdout.clone()),
+      stderr: OutputSink::callback(move |data| {
+        eprint!("[{}] {}", tenant, String::from_utf8_lossy(data));
+      }),
+      ..Default::default()
+    })?;
+
+    worker.eval_blocking::<()>(format!(
+      r#"
+        console.log("Hello from {}");
+        console.error("Something went wrong in {}");
+      "#,
+      tenant, tenant
+    ))?;
+
+    outputs.push((tenant, stdout, worker));
+  }
+
+  // Stopping a worker flushes its output
+  for (tenant, stdout, worker) in outputs {
+    drop(worker);
+    println!("[{}] stdout: {:?}", tenant, stdout.to_string_lossy());
+  }
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
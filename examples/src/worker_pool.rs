@@ -0,0 +1,61 @@
+use edon::NodejsWorkerPoolOptions;
+use edon::WorkerPoolDispatch;
+
+/*
+  The purpose of this example is to demonstrate load balancing work
+  across a pool of Nodejs Worker thread contexts.
+
+  Jobs are submitted from (n) Rust threads to a pool of (size) workers.
+  At most (queue_capacity) jobs are in flight at once, submitting more
+  blocks the submitting thread until a job completes.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  let pool = nodejs.spawn_worker_pool(&NodejsWorkerPoolOptions {
+    size: 4,
+    dispatch: WorkerPoolDispatch::LeastBusy,
+    queue_capacity: 8,
+    ..Default::default()
+  })?;
+
+  // Prepare each context in the pool
+  for worker in pool.workers() {
+    worker.eval_blocking::<()>("globalThis.double = (n) => n * 2")?;
+  }
+
+  let threads = 5;
+  let jobs_per_thread = 100;
+
+  let total = std::thread::scope(|scope| -> anyhow::Result<u32> {
+    let mut handles = vec![];
+
+    for _ in 0..threads {
+      handles.push(scope.spawn(|| -> anyhow::Result<u32> {
+        // Submit all jobs then wait for their results
+        let mut jobs = vec![];
+        for i in 0..jobs_per_thread {
+          jobs.push(pool.eval_job::<u32>(format!("double({})", i))?);
+        }
+
+        let mut sum = 0;
+        for job in jobs {
+          sum += job.wait()?;
+        }
+        Ok(sum)
+      }));
+    }
+
+    let mut total = 0;
+    for handle in handles {
+      total += handle.join().unwrap()?;
+    }
+    Ok(total)
+  })?;
+
+  println!("Summed total: {}", total);
+  assert!(total == threads * (0..jobs_per_thread).map(|i| i * 2).sum::<u32>());
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
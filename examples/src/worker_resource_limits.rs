@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use edon::WorkerEnv;
+use edon::WorkerOptions;
+use edon::WorkerResourceLimits;
+
+/*
+  The purpose of this example is to demonstrate capping the
+  memory of each worker separately.
+
+  The worker has a small heap and its own environment, it allocates
+  until it runs out of memory and the host is told why it exited.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  let worker = nodejs.spawn_worker_thread_with_options(&WorkerOptions {
+    resource_limits: WorkerResourceLimits {
+      max_old_generation_size_mb: Some(32),
+      ..Default::default()
+    },
+    env: WorkerEnv::Custom(HashMap::from([(
+      "TENANT".to_string(),
+      "tenant-a".to_string(),
+    )])),
+    ..Default::default()
+  })?;
+
+  let tenant = worker.eval_blocking::<String>("process.env.TENANT")?;
+  println!("Tenant: {}", tenant); // "tenant-a"
+
+  let result = worker.eval_blocking::<()>(
+    "
+      const data = []
+      while (true) data.push(new Array(100_000).fill('🍝'))
+    ",
+  );
+  println!("Result: {:?}", result); // "Err(WorkerOutOfMemory)"
+
+  if let edon::WorkerStatus::Exited(exit) = worker.status() {
+    println!("Exit: {:?}", exit.error); // "Some(WorkerOutOfMemory)"
+  }
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
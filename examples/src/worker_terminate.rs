@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use edon::WorkerStop;
+
+/*
+  The purpose of this example is to demonstrate stopping workers
+  with a timeout.
+
+  One worker shuts down cleanly while the other is stuck in an
+  infinite loop and has its execution terminated.
+*/
+pub fn main() -> anyhow::Result<()> {
+  let nodejs = edon::Nodejs::load_default(edon::auto_resolve_libnode()?)?;
+
+  let worker = nodejs.spawn_worker_thread()?;
+  worker.eval_blocking::<()>("globalThis.ready = true")?;
+
+  let stop = worker.terminate(Duration::from_secs(1))?;
+  println!("Idle worker: {:?}", stop);
+  assert!(stop == WorkerStop::Clean);
+
+  let worker = nodejs.spawn_worker_thread()?;
+  worker.eval::<&str>("setTimeout(() => { while (true) {} })", |_, _| {})?;
+
+  let stop = worker.terminate(Duration::from_millis(500))?;
+  println!("Stuck worker: {:?}", stop);
+  assert!(stop == WorkerStop::Forced);
+
+  edon::Nodejs::shutdown()?;
+  Ok(())
+}
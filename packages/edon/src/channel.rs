@@ -0,0 +1,266 @@
+use std::future::poll_fn;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+#[cfg(feature = "serde-json")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde-json")]
+use serde::Serialize;
+
+use crate::internal::ContextSender;
+use crate::internal::Mailbox;
+use crate::napi::bindgen_prelude::FromNapiValue;
+use crate::napi::bindgen_prelude::ToNapiValue;
+use crate::napi::JsFunction;
+use crate::napi::JsObject;
+use crate::napi::JsUnknown;
+use crate::napi::NapiValue;
+use crate::napi::Status;
+use crate::prelude::CHANNEL_JS;
+use crate::Env;
+
+/// A value that can be sent through a `Channel`
+///
+/// This is implemented for values that convert to and from napi values,
+/// including `JsTransferable`. Enable the `serde-json` feature to send
+/// any serde type wrapped in `Json`
+pub trait ChannelValue: 'static + Send + Sized {
+  fn into_js(
+    self,
+    env: &Env,
+  ) -> crate::Result<JsUnknown>;
+
+  fn from_js(
+    env: &Env,
+    value: JsUnknown,
+  ) -> crate::Result<Self>;
+}
+
+impl<T: 'static + Send + ToNapiValue + FromNapiValue> ChannelValue for T {
+  fn into_js(
+    self,
+    env: &Env,
+  ) -> crate::Result<JsUnknown> {
+    let value = unsafe { T::to_napi_value(env.raw(), self)? };
+    Ok(unsafe { JsUnknown::from_raw_unchecked(env.raw(), value) })
+  }
+
+  fn from_js(
+    _env: &Env,
+    value: JsUnknown,
+  ) -> crate::Result<Self> {
+    Ok(T::from_unknown(value)?)
+  }
+}
+
+/// Sends a serde value through a `Channel` using `Env::to_js_value`/`Env::from_js_value`
+#[cfg(feature = "serde-json")]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+#[cfg(feature = "serde-json")]
+impl<T: 'static + Send + Serialize + DeserializeOwned> ChannelValue for Json<T> {
+  fn into_js(
+    self,
+    env: &Env,
+  ) -> crate::Result<JsUnknown> {
+    Ok(env.to_js_value(&self.0)?)
+  }
+
+  fn from_js(
+    env: &Env,
+    value: JsUnknown,
+  ) -> crate::Result<Self> {
+    Ok(Json(env.from_js_value(value)?))
+  }
+}
+
+/// Two way message channel between Rust and a JavaScript context
+///
+/// The JavaScript end is an EventEmitter assigned to `globalThis[name]`.
+/// JavaScript sends `In` values to Rust with `postMessage(value)` and receives
+/// `Out` values sent from Rust through the `"message"` event.
+///
+/// Either end can close the channel. Dropping the Rust end closes it
+/// and removes the global
+pub struct Channel<In: ChannelValue, Out: ChannelValue> {
+  name: String,
+  context: ContextSender,
+  mailbox: Arc<Mailbox<In>>,
+  _out: PhantomData<fn(Out)>,
+}
+
+impl<In: ChannelValue, Out: ChannelValue> Channel<In, Out> {
+  pub(crate) fn open(
+    context: ContextSender,
+    name: impl AsRef<str>,
+  ) -> crate::Result<Self> {
+    let name = name.as_ref().to_string();
+    let mailbox = Arc::new(Mailbox::default());
+
+    context.exec_blocking({
+      let name = name.clone();
+      let mailbox = mailbox.clone();
+      move |env| create_js_channel(&env, &name, mailbox)
+    })?;
+
+    Ok(Self {
+      name,
+      context,
+      mailbox,
+      _out: PhantomData,
+    })
+  }
+
+  /// Name of the global the JavaScript end is assigned to
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Returns true once either end has closed the channel
+  pub fn is_closed(&self) -> bool {
+    self.mailbox.is_closed()
+  }
+
+  /// Emit a `"message"` event on the JavaScript end
+  ///
+  /// The value is converted on the JavaScript thread, if that fails
+  /// a `"messageerror"` event is emitted instead. Returns `Error::ChannelClosed`
+  /// once either end has closed the channel or its JavaScript thread has stopped
+  pub fn send(
+    &self,
+    value: Out,
+  ) -> crate::Result<()> {
+    if self.mailbox.is_closed() {
+      return Err(crate::Error::ChannelClosed);
+    }
+
+    let name = self.name.clone();
+    self
+      .context
+      .exec(Box::new(move |env| {
+        emit_message(&env, &name, value).ok();
+        Ok(())
+      }))
+      .map_err(|_| {
+        // Nothing can be posted from a thread that has stopped either
+        self.mailbox.close();
+        crate::Error::ChannelClosed
+      })
+  }
+
+  /// Block the current thread until JavaScript posts a message
+  ///
+  /// Returns `Error::ChannelClosed` once the channel is closed
+  /// and all pending messages have been received
+  pub fn recv(&self) -> crate::Result<In> {
+    self.mailbox.recv().ok_or(crate::Error::ChannelClosed)
+  }
+
+  /// Receive a pending message without blocking
+  pub fn try_recv(&self) -> crate::Result<Option<In>> {
+    match self.mailbox.try_recv() {
+      Some(value) => Ok(Some(value)),
+      None if self.mailbox.is_closed() => Err(crate::Error::ChannelClosed),
+      None => Ok(None),
+    }
+  }
+
+  /// Wait for JavaScript to post a message without blocking the current thread
+  pub async fn recv_async(&self) -> crate::Result<In> {
+    poll_fn(|cx| self.mailbox.poll_recv(cx))
+      .await
+      .ok_or(crate::Error::ChannelClosed)
+  }
+
+  /// Close both ends of the channel
+  pub fn close(self) {}
+}
+
+impl<In: ChannelValue, Out: ChannelValue> Drop for Channel<In, Out> {
+  fn drop(&mut self) {
+    self.mailbox.close();
+
+    let name = self.name.clone();
+    self
+      .context
+      .exec(Box::new(move |env| {
+        close_js_channel(&env, &name).ok();
+        Ok(())
+      }))
+      .ok();
+  }
+}
+
+fn create_js_channel<In: ChannelValue>(
+  env: &Env,
+  name: &str,
+  mailbox: Arc<Mailbox<In>>,
+) -> crate::Result<()> {
+  let mut global = env.get_global()?;
+  if global.has_named_property(name)? {
+    return Err(crate::Error::generic(format!(
+      "Cannot open channel, globalThis.{} is already defined",
+      name
+    )));
+  }
+
+  let post_message = env.create_function_from_closure("postMessage", {
+    let mailbox = mailbox.clone();
+    move |ctx| {
+      let value = if ctx.length > 0 {
+        ctx.get::<JsUnknown>(0)?
+      } else {
+        ctx.env.get_undefined()?.into_unknown()
+      };
+      let value = In::from_js(ctx.env, value)
+        .map_err(|error| crate::napi::Error::new(Status::InvalidArg, error.to_string()))?;
+      mailbox.push(value);
+      ctx.env.get_undefined()
+    }
+  })?;
+
+  let close = env.create_function_from_closure("close", move |ctx| {
+    mailbox.close();
+    ctx.env.get_undefined()
+  })?;
+
+  let create_channel = env.run_script::<_, JsFunction>(CHANNEL_JS)?;
+  let channel = create_channel.call(None, &[post_message, close])?;
+  global.set_named_property(name, channel)?;
+  Ok(())
+}
+
+fn emit_message<Out: ChannelValue>(
+  env: &Env,
+  name: &str,
+  value: Out,
+) -> crate::Result<()> {
+  let channel = env.get_global()?.get_named_property::<JsObject>(name)?;
+  let emit = channel.get_named_property::<JsFunction>("emit")?;
+
+  let args = match value.into_js(env) {
+    Ok(value) => [env.create_string("message")?.into_unknown(), value],
+    Err(error) => [
+      env.create_string("messageerror")?.into_unknown(),
+      env
+        .create_error(crate::napi::Error::from_reason(error.to_string()))?
+        .into_unknown(),
+    ],
+  };
+
+  emit.call(Some(&channel), &args)?;
+  Ok(())
+}
+
+fn close_js_channel(
+  env: &Env,
+  name: &str,
+) -> crate::Result<()> {
+  let mut global = env.get_global()?;
+  let channel = global.get_named_property::<JsObject>(name)?;
+  let close = channel.get_named_property::<JsFunction>("close")?;
+  close.call_without_args(Some(&channel))?;
+  global.delete_named_property(name)?;
+  Ok(())
+}
@@ -2,6 +2,14 @@ use std::sync::Arc;
 
 use libnode_sys::constants::LIB_NAME;
 
+use crate::internal::js_exception_from_value;
+use crate::napi::bindgen_prelude::ToNapiValue;
+use crate::napi::FromPanic;
+use crate::napi::JsUnknown;
+use crate::napi::NapiValue;
+use crate::napi::Status;
+use crate::Env;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Clone)]
@@ -9,13 +17,29 @@ pub enum Error {
   NapiModuleAlreadyRegistered,
   NodejsAlreadyRunning,
   NodejsNotRunning,
+  NodejsShutDown,
+  WorkerPoolQueueFull,
+  WorkerExited,
+  WorkerOutOfMemory,
+  ChannelClosed,
+  Timeout,
   LibnodeNotLoaded,
   LibnodeNotFound,
   LibnodeFailedToLoad,
   LibnodeSymbolNotFound,
   Generic(String),
+  Panic(String),
   IoError(Arc<std::io::Error>),
-  NapiError(crate::napi::Error),
+  NapiError(NapiFailure),
+  JsException {
+    name: String,
+    message: String,
+    stack: Option<String>,
+    cause: Option<Box<Error>>,
+    /// The operation that threw, unset for causes and exceptions
+    /// not returned by an eval, exec, require or import
+    operation: Option<Operation>,
+  },
 }
 
 impl std::fmt::Debug for Error {
@@ -27,13 +51,34 @@ impl std::fmt::Debug for Error {
       Self::NapiModuleAlreadyRegistered => write!(f, "NapiModuleAlreadyRegistered"),
       Self::NodejsAlreadyRunning => write!(f, "NodejsAlreadyRunning"),
       Self::NodejsNotRunning => write!(f, "NodejsNotRunning"),
+      Self::NodejsShutDown => write!(f, "NodejsShutDown"),
+      Self::WorkerPoolQueueFull => write!(f, "WorkerPoolQueueFull"),
+      Self::WorkerExited => write!(f, "WorkerExited"),
+      Self::WorkerOutOfMemory => write!(f, "WorkerOutOfMemory"),
+      Self::ChannelClosed => write!(f, "ChannelClosed"),
+      Self::Timeout => write!(f, "Timeout"),
       Self::LibnodeNotLoaded => write!(f, "LibnodeNotLoaded"),
       Self::LibnodeNotFound => write!(f, "{}", self),
       Self::LibnodeFailedToLoad => write!(f, "LibnodeFailedToLoad"),
       Self::LibnodeSymbolNotFound => write!(f, "LibnodeSymbolNotFound"),
-      Self::Generic(s) => write!(f, "Generic {}", s),
+      Self::Generic(s) => write!(f, "{}", s),
+      Self::Panic(s) => write!(f, "Panic {}", s),
       Self::IoError(arg0) => f.debug_tuple("IoError").field(arg0).finish(),
       Self::NapiError(arg0) => f.debug_tuple("NapiError").field(arg0).finish(),
+      Self::JsException {
+        name,
+        message,
+        stack,
+        cause,
+        operation,
+      } => f
+        .debug_struct("JsException")
+        .field("name", name)
+        .field("message", message)
+        .field("stack", stack)
+        .field("cause", cause)
+        .field("operation", operation)
+        .finish(),
     }
   }
 }
@@ -47,12 +92,23 @@ impl std::fmt::Display for Error {
       Error::NapiModuleAlreadyRegistered => write!(f, "NapiModuleAlreadyRegistered"),
       Error::NodejsAlreadyRunning => write!(f, "AlreadyRunning"),
       Error::NodejsNotRunning => write!(f, "NotRunning"),
+      Error::NodejsShutDown => write!(
+        f,
+        "ShutDown: Nodejs cannot be started again in a process after it was shut down"
+      ),
+      Error::WorkerPoolQueueFull => write!(f, "WorkerPoolQueueFull"),
+      Error::WorkerExited => write!(f, "WorkerExited"),
+      Error::WorkerOutOfMemory => write!(f, "WorkerOutOfMemory"),
+      Error::ChannelClosed => write!(f, "ChannelClosed"),
+      Error::Timeout => write!(f, "Timeout"),
       Error::LibnodeFailedToLoad => write!(f, "LibnodeFailedToLoad"),
       Error::LibnodeNotLoaded => write!(f, "LibnodeNotLoaded"),
       Error::LibnodeSymbolNotFound => write!(f, "LibnodeSymbolNotFound"),
-      Error::Generic(s) => write!(f, "Generic {}", s),
+      Error::Generic(s) => write!(f, "{}", s),
+      Error::Panic(s) => write!(f, "Panic {}", s),
       Error::IoError(err) => write!(f, "{}", err),
-      Error::NapiError(err) => write!(f, "{}", err),
+      Error::NapiError(failure) => write!(f, "{}", failure),
+      Error::JsException { name, message, .. } => write!(f, "{}: {}", name, message),
       Error::LibnodeNotFound => write!(
         f,
         r#"NotFound: {}
@@ -71,9 +127,79 @@ impl Error {
   pub fn generic<S: AsRef<str>>(message: S) -> Self {
     Error::Generic(message.as_ref().to_string())
   }
+
+  /// Converts a napi error into an `Error::NapiError`, capturing the extended
+  /// error info of the failed call and the pending JavaScript exception
+  ///
+  /// Must be called on the JavaScript thread straight after the failed
+  /// call, before other napi calls overwrite the last error info
+  pub fn from_napi(
+    env: &Env,
+    error: crate::napi::Error,
+  ) -> Self {
+    let extended = env
+      .get_last_error_info()
+      .ok()
+      .filter(|info| info.error_code != Status::Ok);
+
+    let exception = if error.maybe_raw.is_null() {
+      env.get_and_clear_last_exception().ok().flatten()
+    } else {
+      // The error holds a reference to the value that was thrown
+      unsafe { crate::napi::Error::to_napi_value(env.raw(), error.clone()) }
+        .ok()
+        .map(|value| unsafe { JsUnknown::from_raw_unchecked(env.raw(), value) })
+    };
+
+    Error::NapiError(NapiFailure {
+      status: error.status,
+      reason: error.reason,
+      extended_message: extended
+        .as_ref()
+        .map(|info| info.message.clone())
+        .filter(|message| !message.is_empty()),
+      engine_error_code: extended.map(|info| info.engine_error_code),
+      exception: exception.map(|value| Box::new(js_exception_from_value(value))),
+      operation: None,
+    })
+  }
+
+  /// Records the operation a napi failure or JavaScript exception
+  /// originated from, other errors are returned unchanged
+  pub fn with_operation(
+    mut self,
+    operation: Operation,
+  ) -> Self {
+    match &mut self {
+      Error::NapiError(failure) => {
+        failure.operation.get_or_insert(operation);
+      }
+      Error::JsException {
+        operation: current, ..
+      } => {
+        current.get_or_insert(operation);
+      }
+      _ => {}
+    }
+    self
+  }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::IoError(error) => Some(error.as_ref()),
+      Error::NapiError(failure) => failure
+        .exception
+        .as_deref()
+        .map(|error| error as &(dyn std::error::Error + 'static)),
+      Error::JsException { cause, .. } => cause
+        .as_deref()
+        .map(|error| error as &(dyn std::error::Error + 'static)),
+      _ => None,
+    }
+  }
+}
 
 impl From<&Error> for Error {
   fn from(value: &Error) -> Self {
@@ -81,13 +207,33 @@ impl From<&Error> for Error {
       Error::NapiModuleAlreadyRegistered => Error::NapiModuleAlreadyRegistered,
       Error::NodejsAlreadyRunning => Error::NodejsAlreadyRunning,
       Error::NodejsNotRunning => Error::NodejsNotRunning,
+      Error::NodejsShutDown => Error::NodejsShutDown,
+      Error::WorkerPoolQueueFull => Error::WorkerPoolQueueFull,
+      Error::WorkerExited => Error::WorkerExited,
+      Error::WorkerOutOfMemory => Error::WorkerOutOfMemory,
+      Error::ChannelClosed => Error::ChannelClosed,
+      Error::Timeout => Error::Timeout,
       Error::LibnodeNotFound => Error::LibnodeNotFound,
       Error::LibnodeNotLoaded => Error::LibnodeNotLoaded,
       Error::LibnodeFailedToLoad => Error::LibnodeFailedToLoad,
       Error::LibnodeSymbolNotFound => Error::LibnodeSymbolNotFound,
       Error::Generic(s) => Error::Generic(s.clone()),
+      Error::Panic(s) => Error::Panic(s.clone()),
       Error::IoError(error) => Error::IoError(error.clone()),
       Error::NapiError(error) => Error::NapiError(error.clone()),
+      Error::JsException {
+        name,
+        message,
+        stack,
+        cause,
+        operation,
+      } => Error::JsException {
+        name: name.clone(),
+        message: message.clone(),
+        stack: stack.clone(),
+        cause: cause.clone(),
+        operation: *operation,
+      },
     }
   }
 }
@@ -98,6 +244,12 @@ impl From<Error> for std::io::Error {
   }
 }
 
+impl FromPanic for Error {
+  fn from_panic(message: String) -> Self {
+    Error::Panic(message)
+  }
+}
+
 impl From<std::io::Error> for Error {
   fn from(value: std::io::Error) -> Self {
     Self::IoError(Arc::new(value))
@@ -106,6 +258,79 @@ impl From<std::io::Error> for Error {
 
 impl From<crate::napi::Error> for Error {
   fn from(value: crate::napi::Error) -> Self {
-    Self::NapiError(value)
+    Self::NapiError(NapiFailure {
+      status: value.status,
+      reason: value.reason,
+      extended_message: None,
+      engine_error_code: None,
+      exception: None,
+      operation: None,
+    })
+  }
+}
+
+/// The operation a napi failure originated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+  Eval,
+  Exec,
+  Require,
+  Import,
+}
+
+impl std::fmt::Display for Operation {
+  fn fmt(
+    &self,
+    f: &mut std::fmt::Formatter<'_>,
+  ) -> std::fmt::Result {
+    match self {
+      Operation::Eval => write!(f, "eval"),
+      Operation::Exec => write!(f, "exec"),
+      Operation::Require => write!(f, "require"),
+      Operation::Import => write!(f, "import"),
+    }
+  }
+}
+
+/// Details of a failed napi call
+///
+/// Errors converted with `Error::from_napi` on the JavaScript thread carry
+/// the extended error info and the JavaScript exception that was pending,
+/// errors converted with `?` only carry the status and reason
+#[derive(Debug, Clone)]
+pub struct NapiFailure {
+  /// Status returned by the napi call
+  pub status: Status,
+  /// Description of the failure
+  pub reason: String,
+  /// Message from `napi_get_last_error_info`
+  pub extended_message: Option<String>,
+  /// Engine specific error code from `napi_get_last_error_info`
+  pub engine_error_code: Option<u32>,
+  /// The JavaScript exception pending when the call failed, with its message and stack
+  pub exception: Option<Box<Error>>,
+  /// The operation the failure originated from
+  pub operation: Option<Operation>,
+}
+
+impl std::fmt::Display for NapiFailure {
+  fn fmt(
+    &self,
+    f: &mut std::fmt::Formatter<'_>,
+  ) -> std::fmt::Result {
+    if let Some(operation) = self.operation {
+      write!(f, "{} failed: ", operation)?;
+    }
+    write!(f, "{}", self.status)?;
+    if !self.reason.is_empty() {
+      write!(f, ", {}", self.reason)?;
+    }
+    if let Some(message) = &self.extended_message {
+      write!(f, " ({})", message)?;
+    }
+    if let Some(exception) = &self.exception {
+      write!(f, ": {}", exception)?;
+    }
+    Ok(())
   }
 }
@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+/// Options for evaluating a block of code
+#[derive(Debug, Default, Clone)]
+pub struct EvalOptions {
+  /// Strip TypeScript types from the code before evaluating it
+  pub typescript: bool,
+  /// When the last line of the script is a Promise, wait for it to settle and
+  /// return the resolved value. A rejection is returned as an `Error::JsException`
+  pub await_promise: bool,
+  /// Interrupt the synchronous part of the evaluation if it runs for longer
+  /// than this, returning `Error::Timeout`. The context remains usable
+  /// though changes made by the script before it was interrupted are kept
+  pub timeout: Option<Duration>,
+}
@@ -0,0 +1,194 @@
+use std::sync::mpsc::channel;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use crate::napi::JsFunction;
+use crate::napi::JsObject;
+use crate::napi::JsUnknown;
+use crate::napi::ValueType;
+use crate::prelude::HEAP_STATISTICS_JS;
+use crate::Env;
+
+/// Memory usage of a Nodejs thread
+///
+/// Every Nodejs context on a thread shares its heap, so these describe
+/// the main thread or the worker as a whole
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeapStatistics {
+  /// Result of `v8.getHeapStatistics()`
+  pub heap: V8HeapStatistics,
+  /// Result of `process.memoryUsage()`
+  pub memory: MemoryUsage,
+  /// Result of `worker.resourceLimits`, this is `None` on the main thread
+  pub resource_limits: Option<ResourceLimits>,
+  /// Result of `performance.eventLoopUtilization()` since the previous
+  /// sample was taken on this thread
+  pub event_loop_utilization: EventLoopUtilization,
+}
+
+/// V8 heap sizes in bytes, see `v8.getHeapStatistics()`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct V8HeapStatistics {
+  pub total_heap_size: u64,
+  pub total_heap_size_executable: u64,
+  pub total_physical_size: u64,
+  pub total_available_size: u64,
+  pub used_heap_size: u64,
+  /// Hard limit of the heap, this reflects `max_old_space_size` and
+  /// `max_semi_space_size`
+  pub heap_size_limit: u64,
+  pub malloced_memory: u64,
+  pub peak_malloced_memory: u64,
+  pub external_memory: u64,
+  pub number_of_native_contexts: u64,
+  pub number_of_detached_contexts: u64,
+}
+
+impl V8HeapStatistics {
+  /// Fraction of `heap_size_limit` that is in use, between 0 and 1
+  pub fn heap_usage(&self) -> f64 {
+    if self.heap_size_limit == 0 {
+      return 0.0;
+    }
+    self.used_heap_size as f64 / self.heap_size_limit as f64
+  }
+}
+
+/// Process memory in bytes, see `process.memoryUsage()`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryUsage {
+  /// Resident set size of the whole process
+  pub rss: u64,
+  pub heap_total: u64,
+  pub heap_used: u64,
+  pub external: u64,
+  pub array_buffers: u64,
+}
+
+/// Heap limits of a worker in MiB, see `worker.resourceLimits`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResourceLimits {
+  pub max_young_generation_size_mb: f64,
+  pub max_old_generation_size_mb: f64,
+  pub code_range_size_mb: f64,
+  pub stack_size_mb: f64,
+}
+
+/// Time the event loop spent idle and active, see `performance.eventLoopUtilization()`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventLoopUtilization {
+  /// Milliseconds spent idle
+  pub idle: f64,
+  /// Milliseconds spent active
+  pub active: f64,
+  /// Fraction of time spent active, between 0 and 1
+  pub utilization: f64,
+}
+
+impl HeapStatistics {
+  /// Collect the heap statistics of the thread `env` belongs to
+  pub fn collect(env: &Env) -> crate::Result<Self> {
+    let heap_statistics = env.run_script::<_, JsFunction>(HEAP_STATISTICS_JS)?;
+    let value = heap_statistics.call_without_args(None)?;
+    let value = value.coerce_to_object()?;
+
+    let heap = value.get_named_property::<JsObject>("heap")?;
+    let memory = value.get_named_property::<JsObject>("memory")?;
+    let resource_limits = value.get_named_property::<JsUnknown>("resourceLimits")?;
+    let event_loop_utilization = value.get_named_property::<JsObject>("eventLoopUtilization")?;
+
+    let resource_limits = match resource_limits.get_type()? {
+      ValueType::Object => {
+        let resource_limits = resource_limits.coerce_to_object()?;
+        Some(ResourceLimits {
+          max_young_generation_size_mb: get_f64(&resource_limits, "maxYoungGenerationSizeMb")?,
+          max_old_generation_size_mb: get_f64(&resource_limits, "maxOldGenerationSizeMb")?,
+          code_range_size_mb: get_f64(&resource_limits, "codeRangeSizeMb")?,
+          stack_size_mb: get_f64(&resource_limits, "stackSizeMb")?,
+        })
+      }
+      _ => None,
+    };
+
+    Ok(Self {
+      heap: V8HeapStatistics {
+        total_heap_size: get_u64(&heap, "total_heap_size")?,
+        total_heap_size_executable: get_u64(&heap, "total_heap_size_executable")?,
+        total_physical_size: get_u64(&heap, "total_physical_size")?,
+        total_available_size: get_u64(&heap, "total_available_size")?,
+        used_heap_size: get_u64(&heap, "used_heap_size")?,
+        heap_size_limit: get_u64(&heap, "heap_size_limit")?,
+        malloced_memory: get_u64(&heap, "malloced_memory")?,
+        peak_malloced_memory: get_u64(&heap, "peak_malloced_memory")?,
+        external_memory: get_u64(&heap, "external_memory")?,
+        number_of_native_contexts: get_u64(&heap, "number_of_native_contexts")?,
+        number_of_detached_contexts: get_u64(&heap, "number_of_detached_contexts")?,
+      },
+      memory: MemoryUsage {
+        rss: get_u64(&memory, "rss")?,
+        heap_total: get_u64(&memory, "heapTotal")?,
+        heap_used: get_u64(&memory, "heapUsed")?,
+        external: get_u64(&memory, "external")?,
+        array_buffers: get_u64(&memory, "arrayBuffers")?,
+      },
+      resource_limits,
+      event_loop_utilization: EventLoopUtilization {
+        idle: get_f64(&event_loop_utilization, "idle")?,
+        active: get_f64(&event_loop_utilization, "active")?,
+        utilization: get_f64(&event_loop_utilization, "utilization")?,
+      },
+    })
+  }
+}
+
+/// Periodically samples the heap statistics of a Nodejs thread
+///
+/// Sampling stops when this is dropped or the thread stops running
+pub struct HeapStatisticsSampler {
+  _stop: Sender<()>,
+}
+
+impl HeapStatisticsSampler {
+  pub(crate) fn start(
+    interval: Duration,
+    sample: impl 'static + Send + Fn() -> crate::Result<HeapStatistics>,
+    is_closed: impl 'static + Send + Fn() -> bool,
+    mut callback: impl 'static + Send + FnMut(crate::Result<HeapStatistics>),
+  ) -> Self {
+    let (tx_stop, rx_stop) = channel::<()>();
+
+    thread::spawn(move || {
+      while let Err(RecvTimeoutError::Timeout) = rx_stop.recv_timeout(interval) {
+        let result = sample();
+        // Any error from a thread that has stopped running is its last sample
+        let stopped = match &result {
+          Ok(_) => false,
+          Err(crate::Error::NodejsNotRunning) => true,
+          Err(_) => is_closed(),
+        };
+        callback(result);
+        if stopped {
+          break;
+        }
+      }
+    });
+
+    Self { _stop: tx_stop }
+  }
+}
+
+fn get_f64(
+  object: &JsObject,
+  name: &str,
+) -> crate::Result<f64> {
+  Ok(object.get_named_property::<f64>(name)?)
+}
+
+fn get_u64(
+  object: &JsObject,
+  name: &str,
+) -> crate::Result<u64> {
+  Ok(get_f64(object, name)? as u64)
+}
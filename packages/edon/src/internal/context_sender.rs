@@ -0,0 +1,43 @@
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use super::ExecCallback;
+use super::NodejsMainEvent;
+use super::NodejsWorkerEvent;
+use super::RequestEvent;
+use super::Requests;
+use super::WorkerState;
+use crate::Env;
+
+/// Sends native code to the JavaScript thread of either the
+/// main Nodejs context or a worker, through its `Requests`
+#[derive(Clone)]
+pub enum ContextSender {
+  Main(Sender<NodejsMainEvent>),
+  Worker(Sender<NodejsWorkerEvent>, Arc<WorkerState>),
+}
+
+impl ContextSender {
+  pub fn exec(
+    &self,
+    callback: ExecCallback,
+  ) -> crate::Result<()> {
+    match self {
+      Self::Main(tx) => Requests::new(tx, None).send(NodejsMainEvent::exec(callback)),
+      Self::Worker(tx, state) => {
+        Requests::new(tx, Some(state)).send(NodejsWorkerEvent::exec(callback))
+      }
+    }
+  }
+
+  /// Run native code on the JavaScript thread and wait for its result
+  pub fn exec_blocking<R: 'static + Send>(
+    &self,
+    callback: impl 'static + Send + FnOnce(Env) -> crate::Result<R>,
+  ) -> crate::Result<R> {
+    match self {
+      Self::Main(tx) => Requests::new(tx, None).exec(callback)?.wait(),
+      Self::Worker(tx, state) => Requests::new(tx, Some(state)).exec(callback)?.wait(),
+    }
+  }
+}
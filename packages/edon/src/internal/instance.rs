@@ -1,4 +1,5 @@
 use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::mpsc::channel;
@@ -7,70 +8,133 @@ use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
+use std::time::Duration;
 
+use super::js_exception_from_value;
 use super::JsTransferable;
+use super::WorkerState;
 use crate::napi::threadsafe_function::ErrorStrategy;
 use crate::napi::threadsafe_function::ThreadsafeFunctionCallMode;
+use crate::napi::JsBuffer;
 use crate::napi::JsFunction;
+use crate::napi::JsNumber;
 use crate::napi::JsString;
 use crate::napi::JsUnknown;
+use crate::napi::ValueType;
 use crate::Env;
-
+use crate::Operation;
+use crate::OutputSink;
+use crate::WorkerEnv;
+use crate::WorkerExit;
+use crate::WorkerOptions;
+use crate::WorkerStop;
+
+// Set by the first start and never reset. libnode cannot be initialized again
+// once it has been torn down, so Nodejs::load reports Error::NodejsShutDown
+// rather than starting a second instance after Nodejs::shutdown()
 static STARTED: AtomicBool = AtomicBool::new(false);
 
+pub type ExecCallback = Box<dyn 'static + Send + FnOnce(Env) -> crate::Result<()>>;
+pub type EvalCallback = Box<dyn 'static + Send + FnOnce(Env, crate::Result<JsUnknown>)>;
+pub type ResultCallback = Box<dyn 'static + Send + FnOnce(crate::Result<()>)>;
+
+/// Requests for a worker and its state, handed to the worker's dispatch thread
+type WorkerReceiver = Mutex<Option<(Receiver<NodejsWorkerEvent>, Arc<WorkerState>)>>;
+
 pub enum NodejsMainEvent {
   Exec {
-    callback: Box<dyn Send + FnOnce(Env) -> crate::Result<()>>,
+    callback: ExecCallback,
   },
   StopMain {
+    timeout: Duration,
     resolve: Sender<()>,
   },
   Eval {
     code: String,
-    callback: Box<dyn 'static + Send + FnOnce(Env, JsUnknown)>,
+    await_promise: bool,
+    timeout: Option<Duration>,
+    callback: EvalCallback,
   },
   EvalTypeScript {
     code: String,
-    callback: Box<dyn 'static + Send + FnOnce(Env, JsUnknown)>,
+    await_promise: bool,
+    timeout: Option<Duration>,
+    callback: EvalCallback,
   },
   Require {
     specifier: String,
-    resolve: Sender<crate::Result<()>>,
+    resolve: ResultCallback,
   },
   Import {
     specifier: String,
-    resolve: Sender<crate::Result<()>>,
+    resolve: ResultCallback,
   },
   StartWorker {
     rx_wrk: Receiver<NodejsWorkerEvent>,
-    argv: Vec<String>,
-    resolve: Sender<String>,
+    options: WorkerOptions,
+    state: Arc<WorkerState>,
+    resolve: Sender<crate::Result<String>>,
   },
+  /// Resolves to `None` if the worker had already exited
   StopWorker {
     id: String,
-    resolve: Sender<()>,
+    timeout: Duration,
+    resolve: Sender<Option<WorkerStop>>,
   },
 }
 
 pub enum NodejsWorkerEvent {
   Exec {
-    callback: Box<dyn Send + FnOnce(Env) -> crate::Result<()>>,
+    callback: ExecCallback,
   },
   Eval {
     code: String,
-    callback: Box<dyn 'static + Send + FnOnce(Env, JsUnknown)>,
+    await_promise: bool,
+    timeout: Option<Duration>,
+    callback: EvalCallback,
   },
   EvalTypeScript {
     code: String,
-    callback: Box<dyn 'static + Send + FnOnce(Env, JsUnknown)>,
+    await_promise: bool,
+    timeout: Option<Duration>,
+    callback: EvalCallback,
   },
   Require {
     specifier: String,
-    resolve: Sender<crate::Result<()>>,
+    resolve: ResultCallback,
   },
   Import {
     specifier: String,
-    resolve: Sender<crate::Result<()>>,
+    resolve: ResultCallback,
+  },
+  CreateContext {
+    globals: Vec<String>,
+    callback: EvalCallback,
+  },
+  DestroyContext {
+    id: String,
+  },
+  ContextEval {
+    id: String,
+    code: String,
+    typescript: bool,
+    await_promise: bool,
+    timeout: Option<Duration>,
+    callback: EvalCallback,
+  },
+  ContextExec {
+    id: String,
+    callback: EvalCallback,
+  },
+  ContextRequire {
+    id: String,
+    specifier: String,
+    resolve: ResultCallback,
+  },
+  ContextImport {
+    id: String,
+    specifier: String,
+    resolve: ResultCallback,
   },
 }
 
@@ -87,7 +151,11 @@ pub fn start_node_instance<Args: AsRef<str>>(
   let (tx, rx) = channel();
   let rx: Arc<Mutex<Option<Receiver<NodejsMainEvent>>>> = Arc::new(Mutex::new(Some(rx)));
 
+  super::register_modules_binding()?;
+
   super::napi_module_register("edon:main", move |env, mut exports| {
+    super::set_thread_env(env);
+
     let js_on_event = env.create_function_from_closure("edon::main::onEvent", {
       let rx = rx.clone();
       move |ctx| {
@@ -98,12 +166,17 @@ pub fn start_node_instance<Args: AsRef<str>>(
             0,
             move |ctx| match ctx.value {
               NodejsMainEvent::Exec { callback } => {
-                callback(ctx.env).unwrap();
+                // There is no caller to return the error of a fire-and-forget callback to and
+                // failing this fatal threadsafe function would abort the process, so it is logged
+                if let Err(error) = super::exec_result(&ctx.env, || callback(ctx.env)) {
+                  eprintln!("edon: exec callback failed: {}", error);
+                }
                 Ok(vec![])
               }
-              NodejsMainEvent::StopMain { resolve } => {
+              NodejsMainEvent::StopMain { timeout, resolve } => {
                 let action = ctx.env.create_uint32(0)?.into_unknown();
-                let payload = ctx.env.get_undefined()?.into_unknown();
+                // timeoutMs
+                let payload = create_timeout_ms(&ctx.env, timeout)?.into_unknown();
                 let resolve = ctx
                   .env
                   .create_function_from_closure("NodejsEvent::done", move |ctx| {
@@ -113,126 +186,124 @@ pub fn start_node_instance<Args: AsRef<str>>(
                   .into_unknown();
                 Ok(vec![action, payload, resolve])
               }
-              NodejsMainEvent::Eval { code, callback } => {
+              NodejsMainEvent::Eval {
+                code,
+                await_promise,
+                timeout,
+                callback,
+              } => {
                 let action = ctx.env.create_uint32(1)?.into_unknown();
-                let payload = ctx.env.create_string(&code)?.into_unknown();
-                let callback = {
-                  let cell = Cell::new(Some(callback));
-                  move |env, val| {
-                    let func = cell
-                      .take()
-                      .expect("This function should not be called more than once");
-                    func(env, val)
-                  }
-                };
-                let resolve = ctx
-                  .env
-                  .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                    let ret = if ctx.length > 0 {
-                      ctx.get(0)?
-                    } else {
-                      ctx.env.get_undefined()?.into_unknown()
-                    };
-                    callback(*ctx.env, ret);
-                    ctx.env.get_undefined()
-                  })?
-                  .into_unknown();
+                let payload = create_eval_payload(&ctx.env, &code, await_promise, timeout)?;
+                let [resolve, reject] = create_eval_callbacks(&ctx.env, Operation::Eval, callback)?;
 
-                Ok(vec![action, payload, resolve])
+                Ok(vec![action, payload, resolve, reject])
               }
-              NodejsMainEvent::EvalTypeScript { code, callback } => {
+              NodejsMainEvent::EvalTypeScript {
+                code,
+                await_promise,
+                timeout,
+                callback,
+              } => {
                 let action = ctx.env.create_uint32(2)?.into_unknown();
-                let payload = ctx.env.create_string(&code)?.into_unknown();
-                let callback = {
-                  let cell = Cell::new(Some(callback));
-                  move |env, val| {
-                    let func = cell
-                      .take()
-                      .expect("This function should not be called more than once");
-                    func(env, val)
-                  }
-                };
-                let resolve = ctx
-                  .env
-                  .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                    let ret = if ctx.length > 0 {
-                      ctx.get(0)?
-                    } else {
-                      ctx.env.get_undefined()?.into_unknown()
-                    };
-                    callback(*ctx.env, ret);
-                    ctx.env.get_undefined()
-                  })?
-                  .into_unknown();
+                let payload = create_eval_payload(&ctx.env, &code, await_promise, timeout)?;
+                let [resolve, reject] = create_eval_callbacks(&ctx.env, Operation::Eval, callback)?;
 
-                Ok(vec![action, payload, resolve])
+                Ok(vec![action, payload, resolve, reject])
               }
               NodejsMainEvent::Require { specifier, resolve } => {
                 let action = ctx.env.create_uint32(3)?.into_unknown();
                 let payload = ctx.env.create_string(&specifier)?.into_unknown();
-                let resolve = ctx
-                  .env
-                  .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                    resolve.send(Ok(())).unwrap();
-                    ctx.env.get_undefined()
-                  })?
-                  .into_unknown();
+                let [resolve, reject] =
+                  create_result_callbacks(&ctx.env, Operation::Require, resolve)?;
 
-                Ok(vec![action, payload, resolve])
+                Ok(vec![action, payload, resolve, reject])
               }
               NodejsMainEvent::Import { specifier, resolve } => {
                 let action = ctx.env.create_uint32(4)?.into_unknown();
                 let payload = ctx.env.create_string(&specifier)?.into_unknown();
-                let resolve = ctx
-                  .env
-                  .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                    resolve.send(Ok(())).unwrap();
-                    ctx.env.get_undefined()
-                  })?
-                  .into_unknown();
+                let [resolve, reject] =
+                  create_result_callbacks(&ctx.env, Operation::Import, resolve)?;
 
-                Ok(vec![action, payload, resolve])
+                Ok(vec![action, payload, resolve, reject])
               }
               NodejsMainEvent::StartWorker {
                 rx_wrk,
-                argv,
+                options,
+                state,
                 resolve,
               } => {
                 let action = ctx.env.create_uint32(5)?.into_unknown();
 
-                // [argv, tx_worker]
-                let mut payload = ctx.env.create_array(2)?;
-
-                let mut argv_js = ctx.env.create_array(0)?;
-                for (i, v) in argv.iter().enumerate() {
-                  argv_js.set(i as u32, ctx.env.create_string(v)?)?;
-                }
-
-                payload.set(0, argv_js)?;
+                // [options, tx_worker, onStdout, onStderr, onExit]
+                let mut payload = ctx.env.create_array(5)?;
+                payload.set(0, create_worker_options(&ctx.env, &options)?)?;
                 payload.set(
                   1,
-                  JsTransferable::new(Mutex::new(Some(rx_wrk))).into_unknown(&ctx.env)?,
+                  JsTransferable::new(Mutex::new(Some((rx_wrk, state.clone()))))
+                    .into_unknown(&ctx.env)?,
                 )?;
+                payload.set(2, create_output_callback(&ctx.env, options.stdout)?)?;
+                payload.set(3, create_output_callback(&ctx.env, options.stderr)?)?;
+                payload.set(4, create_exit_callback(&ctx.env, state)?)?;
                 let payload = payload.coerce_to_object()?.into_unknown();
 
-                let resolve = ctx
+                let resolve = Rc::new(Cell::new(Some(resolve)));
+
+                let done = ctx
                   .env
-                  .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                    let id = ctx.get::<JsString>(0)?;
-                    resolve.send(id.into_utf8()?.as_str()?.to_string()).unwrap();
+                  .create_function_from_closure("NodejsEvent::done", {
+                    let resolve = resolve.clone();
+                    move |ctx| {
+                      let id = ctx.get::<JsString>(0)?.into_utf8()?.as_str()?.to_string();
+                      if let Some(resolve) = resolve.take() {
+                        resolve.send(Ok(id)).ok();
+                      }
+                      ctx.env.get_undefined()
+                    }
+                  })?
+                  .into_unknown();
+
+                // The Worker constructor throws for invalid options, e.g. execArgv
+                let reject = ctx
+                  .env
+                  .create_function_from_closure("NodejsEvent::reject", move |ctx| {
+                    let error = js_exception_from_value(ctx.get(0)?);
+                    if let Some(resolve) = resolve.take() {
+                      resolve.send(Err(error)).ok();
+                    }
                     ctx.env.get_undefined()
                   })?
                   .into_unknown();
 
-                Ok(vec![action, payload, resolve])
+                Ok(vec![action, payload, done, reject])
               }
-              NodejsMainEvent::StopWorker { id, resolve } => {
+              NodejsMainEvent::StopWorker {
+                id,
+                timeout,
+                resolve,
+              } => {
                 let action = ctx.env.create_uint32(6)?.into_unknown();
-                let payload = ctx.env.create_string(&id)?.into_unknown();
+
+                // [id, timeoutMs]
+                let mut payload = ctx.env.create_array(2)?;
+                payload.set(0, ctx.env.create_string(&id)?)?;
+                payload.set(1, ctx.env.create_double(timeout.as_millis() as f64)?)?;
+                let payload = payload.coerce_to_object()?.into_unknown();
+
                 let resolve = ctx
                   .env
                   .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                    resolve.send(()).unwrap();
+                    // forced | undefined if the worker had already exited
+                    let forced = ctx.get::<Option<bool>>(0)?;
+                    resolve
+                      .send(forced.map(|forced| {
+                        if forced {
+                          WorkerStop::Forced
+                        } else {
+                          WorkerStop::Clean
+                        }
+                      }))
+                      .unwrap();
                     ctx.env.get_undefined()
                   })?
                   .into_unknown();
@@ -249,8 +320,14 @@ pub fn start_node_instance<Args: AsRef<str>>(
               panic!("Cannot run twice")
             };
 
+            // Releasing the threadsafe function after StopMain lets the event
+            // loop exit, later requests fail as the receiver is dropped
             while let Ok(event) = rx.recv() {
+              let stop = matches!(event, NodejsMainEvent::StopMain { .. });
               on_eval.call(event, ThreadsafeFunctionCallMode::Blocking);
+              if stop {
+                break;
+              }
             }
           }
         });
@@ -265,6 +342,8 @@ pub fn start_node_instance<Args: AsRef<str>>(
   })?;
 
   super::napi_module_register("edon:worker", move |env, mut exports| {
+    super::set_thread_env(env);
+
     let js_on_event = env.create_function_from_closure("edon::main::onEvent", |ctx| {
       let callback = ctx.get::<JsFunction>(1)?;
 
@@ -273,99 +352,152 @@ pub fn start_node_instance<Args: AsRef<str>>(
           0,
           move |ctx| match ctx.value {
             NodejsWorkerEvent::Exec { callback } => {
-              callback(ctx.env).unwrap();
+              // There is no caller to return the error of a fire-and-forget callback to and
+              // failing this fatal threadsafe function would abort the process, so it is logged
+              if let Err(error) = super::exec_result(&ctx.env, || callback(ctx.env)) {
+                eprintln!("edon: exec callback failed: {}", error);
+              }
               Ok(vec![])
             }
-            NodejsWorkerEvent::Eval { code, callback } => {
+            NodejsWorkerEvent::Eval {
+              code,
+              await_promise,
+              timeout,
+              callback,
+            } => {
               let action = ctx.env.create_uint32(0)?.into_unknown();
-              let payload = ctx.env.create_string(&code)?.into_unknown();
-              let callback = {
-                let cell = Cell::new(Some(callback));
-                move |env, val| {
-                  let func = cell
-                    .take()
-                    .expect("This function should not be called more than once");
-                  func(env, val)
-                }
-              };
-              let resolve = ctx
-                .env
-                .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                  let ret = if ctx.length > 0 {
-                    ctx.get(0)?
-                  } else {
-                    ctx.env.get_undefined()?.into_unknown()
-                  };
-                  callback(*ctx.env, ret);
-                  ctx.env.get_undefined()
-                })?
-                .into_unknown();
-
-              Ok(vec![action, payload, resolve])
+              let payload = create_eval_payload(&ctx.env, &code, await_promise, timeout)?;
+              let [resolve, reject] = create_eval_callbacks(&ctx.env, Operation::Eval, callback)?;
+
+              Ok(vec![action, payload, resolve, reject])
             }
-            NodejsWorkerEvent::EvalTypeScript { code, callback } => {
+            NodejsWorkerEvent::EvalTypeScript {
+              code,
+              await_promise,
+              timeout,
+              callback,
+            } => {
               let action = ctx.env.create_uint32(1)?.into_unknown();
-              let payload = ctx.env.create_string(&code)?.into_unknown();
-              let callback = {
-                let cell = Cell::new(Some(callback));
-                move |env, val| {
-                  let func = cell
-                    .take()
-                    .expect("This function should not be called more than once");
-                  func(env, val)
-                }
-              };
-              let resolve = ctx
-                .env
-                .create_function_from_closure("NodejsEvent::done", move |ctx| {
-                  let ret = if ctx.length > 0 {
-                    ctx.get(0)?
-                  } else {
-                    ctx.env.get_undefined()?.into_unknown()
-                  };
-                  callback(*ctx.env, ret);
-                  ctx.env.get_undefined()
-                })?
-                .into_unknown();
-
-              Ok(vec![action, payload, resolve])
+              let payload = create_eval_payload(&ctx.env, &code, await_promise, timeout)?;
+              let [resolve, reject] = create_eval_callbacks(&ctx.env, Operation::Eval, callback)?;
+
+              Ok(vec![action, payload, resolve, reject])
             }
             NodejsWorkerEvent::Require { specifier, resolve } => {
               let action = ctx.env.create_uint32(2)?.into_unknown();
               let payload = ctx.env.create_string(&specifier)?.into_unknown();
-              let resolve = ctx
-                .env
-                .create_function_from_closure("NodejsContextEvent::done", move |ctx| {
-                  resolve.send(Ok(())).unwrap();
-                  ctx.env.get_undefined()
-                })?
-                .into_unknown();
-
-              Ok(vec![action, payload, resolve])
+              let [resolve, reject] =
+                create_result_callbacks(&ctx.env, Operation::Require, resolve)?;
+
+              Ok(vec![action, payload, resolve, reject])
             }
             NodejsWorkerEvent::Import { specifier, resolve } => {
               let action = ctx.env.create_uint32(3)?.into_unknown();
               let payload = ctx.env.create_string(&specifier)?.into_unknown();
-              let resolve = ctx
-                .env
-                .create_function_from_closure("NodejsContextEvent::done", move |ctx| {
-                  resolve.send(Ok(())).unwrap();
-                  ctx.env.get_undefined()
-                })?
-                .into_unknown();
-
-              Ok(vec![action, payload, resolve])
+              let [resolve, reject] =
+                create_result_callbacks(&ctx.env, Operation::Import, resolve)?;
+
+              Ok(vec![action, payload, resolve, reject])
+            }
+            NodejsWorkerEvent::CreateContext { globals, callback } => {
+              let action = ctx.env.create_uint32(4)?.into_unknown();
+
+              // [...globals]
+              let mut payload = ctx.env.create_array(0)?;
+              for (i, name) in globals.iter().enumerate() {
+                payload.set(i as u32, ctx.env.create_string(name)?)?;
+              }
+              let payload = payload.coerce_to_object()?.into_unknown();
+              let [resolve, reject] = create_eval_callbacks(&ctx.env, Operation::Exec, callback)?;
+
+              Ok(vec![action, payload, resolve, reject])
+            }
+            NodejsWorkerEvent::DestroyContext { id } => {
+              let action = ctx.env.create_uint32(5)?.into_unknown();
+              let payload = ctx.env.create_string(&id)?.into_unknown();
+
+              Ok(vec![action, payload])
+            }
+            NodejsWorkerEvent::ContextEval {
+              id,
+              code,
+              typescript,
+              await_promise,
+              timeout,
+              callback,
+            } => {
+              let action = ctx.env.create_uint32(6)?.into_unknown();
+
+              // [id, code, typescript, awaitPromise, timeoutMs]
+              let mut payload = ctx.env.create_array(5)?;
+              payload.set(0, ctx.env.create_string(&id)?)?;
+              payload.set(1, ctx.env.create_string(&code)?)?;
+              payload.set(2, ctx.env.get_boolean(typescript)?)?;
+              payload.set(3, ctx.env.get_boolean(await_promise)?)?;
+              if let Some(timeout) = timeout {
+                payload.set(4, create_timeout_ms(&ctx.env, timeout)?)?;
+              }
+              let payload = payload.coerce_to_object()?.into_unknown();
+              let [resolve, reject] = create_eval_callbacks(&ctx.env, Operation::Eval, callback)?;
+
+              Ok(vec![action, payload, resolve, reject])
+            }
+            NodejsWorkerEvent::ContextExec { id, callback } => {
+              let action = ctx.env.create_uint32(7)?.into_unknown();
+              let payload = ctx.env.create_string(&id)?.into_unknown();
+              let [resolve, reject] = create_eval_callbacks(&ctx.env, Operation::Exec, callback)?;
+
+              Ok(vec![action, payload, resolve, reject])
+            }
+            NodejsWorkerEvent::ContextRequire {
+              id,
+              specifier,
+              resolve,
+            } => {
+              let action = ctx.env.create_uint32(8)?.into_unknown();
+
+              // [id, specifier]
+              let mut payload = ctx.env.create_array(2)?;
+              payload.set(0, ctx.env.create_string(&id)?)?;
+              payload.set(1, ctx.env.create_string(&specifier)?)?;
+              let payload = payload.coerce_to_object()?.into_unknown();
+              let [resolve, reject] =
+                create_result_callbacks(&ctx.env, Operation::Require, resolve)?;
+
+              Ok(vec![action, payload, resolve, reject])
+            }
+            NodejsWorkerEvent::ContextImport {
+              id,
+              specifier,
+              resolve,
+            } => {
+              let action = ctx.env.create_uint32(9)?.into_unknown();
+
+              // [id, specifier]
+              let mut payload = ctx.env.create_array(2)?;
+              payload.set(0, ctx.env.create_string(&id)?)?;
+              payload.set(1, ctx.env.create_string(&specifier)?)?;
+              let payload = payload.coerce_to_object()?.into_unknown();
+              let [resolve, reject] =
+                create_result_callbacks(&ctx.env, Operation::Import, resolve)?;
+
+              Ok(vec![action, payload, resolve, reject])
             }
           },
         )?;
 
-      let rx = ctx.get::<JsTransferable<Mutex<Option<Receiver<NodejsWorkerEvent>>>>>(0)?;
-      let rx = rx.take()?.lock().unwrap().take().unwrap();
+      let rx = ctx.get::<JsTransferable<WorkerReceiver>>(0)?;
+      let (rx, state) = rx.take()?.lock().unwrap().take().unwrap();
 
       thread::spawn({
         move || {
           while let Ok(event) = rx.recv() {
-            on_eval.call(event, ThreadsafeFunctionCallMode::Blocking);
+            // The worker has stopped. It is marked so later requests fail without being
+            // sent, the dropped request waits for the main thread to record why it exited
+            if let Err(event) = on_eval.try_call(event, ThreadsafeFunctionCallMode::Blocking) {
+              state.mark_exited();
+              drop(event);
+            }
           }
         }
       });
@@ -389,3 +521,204 @@ pub fn start_node_instance<Args: AsRef<str>>(
 
   Ok(tx)
 }
+
+/// Creates the function a worker's output stream is forwarded to.
+/// Inherited output is left to the prelude and passed as `undefined`
+fn create_output_callback(
+  env: &Env,
+  sink: OutputSink,
+) -> crate::napi::Result<JsUnknown> {
+  if sink.is_inherit() {
+    return Ok(env.get_undefined()?.into_unknown());
+  }
+
+  Ok(
+    env
+      .create_function_from_closure("NodejsWorker::output", move |ctx| {
+        let data = ctx.get::<JsBuffer>(0)?.into_value()?;
+        sink.write(data.as_ref());
+        ctx.env.get_undefined()
+      })?
+      .into_unknown(),
+  )
+}
+
+/// Creates the `{ argv, execArgv, env, shareEnv, cwd, resourceLimits }` options for a worker
+fn create_worker_options(
+  env: &Env,
+  options: &WorkerOptions,
+) -> crate::napi::Result<JsUnknown> {
+  let mut worker_options = env.create_object()?;
+
+  let mut argv = env.create_array(0)?;
+  for (i, arg) in options.argv.iter().enumerate() {
+    argv.set(i as u32, env.create_string(arg)?)?;
+  }
+  worker_options.set_named_property("argv", argv)?;
+
+  if let Some(exec_argv) = &options.exec_argv {
+    let mut exec_argv_js = env.create_array(0)?;
+    for (i, arg) in exec_argv.iter().enumerate() {
+      exec_argv_js.set(i as u32, env.create_string(arg)?)?;
+    }
+    worker_options.set_named_property("execArgv", exec_argv_js)?;
+  }
+
+  match &options.env {
+    WorkerEnv::Inherit => {}
+    WorkerEnv::Share => worker_options.set_named_property("shareEnv", env.get_boolean(true)?)?,
+    WorkerEnv::Custom(vars) => {
+      let mut env_js = env.create_object()?;
+      for (key, value) in vars {
+        env_js.set_named_property(key, env.create_string(value)?)?;
+      }
+      worker_options.set_named_property("env", env_js)?;
+    }
+  }
+
+  if let Some(cwd) = &options.cwd {
+    let cwd = std::path::absolute(cwd)?;
+    worker_options.set_named_property("cwd", env.create_string(&cwd.to_string_lossy())?)?;
+  }
+
+  let limits = &options.resource_limits;
+  let mut resource_limits = env.create_object()?;
+  for (name, limit) in [
+    ("maxOldGenerationSizeMb", limits.max_old_generation_size_mb),
+    (
+      "maxYoungGenerationSizeMb",
+      limits.max_young_generation_size_mb,
+    ),
+    ("codeRangeSizeMb", limits.code_range_size_mb),
+    ("stackSizeMb", limits.stack_size_mb),
+  ] {
+    if let Some(limit) = limit {
+      resource_limits.set_named_property(name, env.create_uint32(limit)?)?;
+    }
+  }
+  worker_options.set_named_property("resourceLimits", resource_limits)?;
+
+  Ok(worker_options.into_unknown())
+}
+
+/// Creates the function called with `(code, error, outOfMemory)` when a worker exits
+fn create_exit_callback(
+  env: &Env,
+  state: Arc<WorkerState>,
+) -> crate::napi::Result<JsUnknown> {
+  Ok(
+    env
+      .create_function_from_closure("NodejsWorker::exit", move |ctx| {
+        let code = ctx.get::<i32>(0)?;
+        let error = ctx.get::<JsUnknown>(1)?;
+        let out_of_memory = ctx.get::<bool>(2)?;
+        let error = match error.get_type()? {
+          ValueType::Undefined => None,
+          _ if out_of_memory => Some(Box::new(crate::Error::WorkerOutOfMemory)),
+          _ => Some(Box::new(js_exception_from_value(error))),
+        };
+        state.exit(WorkerExit { code, error });
+        ctx.env.get_undefined()
+      })?
+      .into_unknown(),
+  )
+}
+
+/// Creates the `[code, awaitPromise, timeoutMs]` payload for an evaluation
+fn create_eval_payload(
+  env: &Env,
+  code: &str,
+  await_promise: bool,
+  timeout: Option<Duration>,
+) -> crate::napi::Result<JsUnknown> {
+  let mut payload = env.create_array(3)?;
+  payload.set(0, env.create_string(code)?)?;
+  payload.set(1, env.get_boolean(await_promise)?)?;
+  if let Some(timeout) = timeout {
+    payload.set(2, create_timeout_ms(env, timeout)?)?;
+  }
+  Ok(payload.coerce_to_object()?.into_unknown())
+}
+
+/// Converts a timeout into whole milliseconds, vm.Script requires at least 1ms
+fn create_timeout_ms(
+  env: &Env,
+  timeout: Duration,
+) -> crate::napi::Result<JsNumber> {
+  env.create_double(timeout.as_millis().max(1) as f64)
+}
+
+/// Creates the `done` and `reject` functions handed to the prelude
+/// for an evaluation. Only the first of the two to be called has an effect,
+/// failures are attributed to `operation`
+fn create_eval_callbacks(
+  env: &Env,
+  operation: Operation,
+  callback: EvalCallback,
+) -> crate::napi::Result<[JsUnknown; 2]> {
+  let callback = Rc::new(Cell::new(Some(callback)));
+
+  let resolve = env
+    .create_function_from_closure("NodejsEvent::done", {
+      let callback = callback.clone();
+      move |ctx| {
+        let ret = if ctx.length > 0 {
+          ctx.get(0)?
+        } else {
+          ctx.env.get_undefined()?.into_unknown()
+        };
+        if let Some(callback) = callback.take() {
+          callback(*ctx.env, Ok(ret));
+        }
+        ctx.env.get_undefined()
+      }
+    })?
+    .into_unknown();
+
+  let reject = env
+    .create_function_from_closure("NodejsEvent::reject", move |ctx| {
+      let error = js_exception_from_value(ctx.get(0)?).with_operation(operation);
+      if let Some(callback) = callback.take() {
+        callback(*ctx.env, Err(error));
+      }
+      ctx.env.get_undefined()
+    })?
+    .into_unknown();
+
+  Ok([resolve, reject])
+}
+
+/// Creates the `done` and `reject` functions handed to the prelude
+/// for requests that only report success or failure, failures are
+/// attributed to `operation`
+fn create_result_callbacks(
+  env: &Env,
+  operation: Operation,
+  callback: ResultCallback,
+) -> crate::napi::Result<[JsUnknown; 2]> {
+  let callback = Rc::new(Cell::new(Some(callback)));
+
+  let resolve = env
+    .create_function_from_closure("NodejsEvent::done", {
+      let callback = callback.clone();
+      move |ctx| {
+        if let Some(callback) = callback.take() {
+          callback(Ok(()));
+        }
+        ctx.env.get_undefined()
+      }
+    })?
+    .into_unknown();
+
+  let reject = env
+    .create_function_from_closure("NodejsEvent::reject", move |ctx| {
+      let error = js_exception_from_value(ctx.get(0)?).with_operation(operation);
+      if let Some(callback) = callback.take() {
+        callback(Err(error));
+      }
+      ctx.env.get_undefined()
+    })?
+    .into_unknown();
+
+  Ok([resolve, reject])
+}
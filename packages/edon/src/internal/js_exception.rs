@@ -0,0 +1,71 @@
+use crate::napi::JsObject;
+use crate::napi::JsUnknown;
+use crate::napi::ValueType;
+
+// Guards against error objects that reference themselves as their cause
+const MAX_CAUSE_DEPTH: usize = 8;
+
+// Error code of the exception thrown when a vm.Script exceeds its timeout
+const SCRIPT_EXECUTION_TIMEOUT: &str = "ERR_SCRIPT_EXECUTION_TIMEOUT";
+
+/// Converts a thrown JavaScript value into a `crate::Error::JsException`
+///
+/// Values that are not objects (e.g. `throw "oops"`) are stringified
+/// and reported as a generic `Error`. Script timeouts are reported
+/// as `crate::Error::Timeout`
+pub fn js_exception_from_value(value: JsUnknown) -> crate::Error {
+  match try_js_exception_from_value(value, 0) {
+    Ok(error) => error,
+    Err(error) => error.into(),
+  }
+}
+
+fn try_js_exception_from_value(
+  value: JsUnknown,
+  depth: usize,
+) -> crate::napi::Result<crate::Error> {
+  if value.get_type()? != ValueType::Object {
+    return Ok(crate::Error::JsException {
+      name: "Error".to_string(),
+      message: value.coerce_to_string()?.into_utf8()?.into_owned()?,
+      stack: None,
+      cause: None,
+      operation: None,
+    });
+  }
+
+  let error = value.coerce_to_object()?;
+
+  if get_string_property(&error, "code")?.as_deref() == Some(SCRIPT_EXECUTION_TIMEOUT) {
+    return Ok(crate::Error::Timeout);
+  }
+
+  let cause = if depth < MAX_CAUSE_DEPTH {
+    let cause = error.get_named_property::<JsUnknown>("cause")?;
+    match cause.get_type()? {
+      ValueType::Undefined => None,
+      _ => Some(Box::new(try_js_exception_from_value(cause, depth + 1)?)),
+    }
+  } else {
+    None
+  };
+
+  Ok(crate::Error::JsException {
+    name: get_string_property(&error, "name")?.unwrap_or_else(|| "Error".to_string()),
+    message: get_string_property(&error, "message")?.unwrap_or_default(),
+    stack: get_string_property(&error, "stack")?,
+    cause,
+    operation: None,
+  })
+}
+
+fn get_string_property(
+  object: &JsObject,
+  key: &str,
+) -> crate::napi::Result<Option<String>> {
+  let value = object.get_named_property::<JsUnknown>(key)?;
+  match value.get_type()? {
+    ValueType::Undefined | ValueType::Null => Ok(None),
+    _ => Ok(Some(value.coerce_to_string()?.into_utf8()?.into_owned()?)),
+  }
+}
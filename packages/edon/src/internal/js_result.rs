@@ -0,0 +1,75 @@
+use std::any::TypeId;
+
+use super::js_exception_from_value;
+use crate::napi::bindgen_prelude::FromNapiValue;
+use crate::napi::catch_panic;
+use crate::napi::JsUnknown;
+use crate::Env;
+use crate::Operation;
+
+/// Converts the value produced by an operation into a Rust value on the
+/// JavaScript thread, recording the operation and napi details on failure
+pub fn from_js_result<T: 'static + FromNapiValue>(
+  env: &Env,
+  operation: Operation,
+  value: crate::Result<JsUnknown>,
+) -> crate::Result<T> {
+  discard_unit::<T>(env, value)
+    .and_then(|value| T::from_unknown(value).map_err(|error| crate::Error::from_napi(env, error)))
+    .map_err(|error| error.with_operation(operation))
+}
+
+/// Deserializes the value produced by an operation into a Rust value on the
+/// JavaScript thread, recording the operation and napi details on failure
+#[cfg(feature = "serde-json")]
+pub fn from_js_result_json<T: 'static + serde::de::DeserializeOwned>(
+  env: &Env,
+  operation: Operation,
+  value: crate::Result<JsUnknown>,
+) -> crate::Result<T> {
+  discard_unit::<T>(env, value)
+    .and_then(|value| {
+      env
+        .from_js_value(value)
+        .map_err(|error| crate::Error::from_napi(env, error))
+    })
+    .map_err(|error| error.with_operation(operation))
+}
+
+/// Replaces the value with `undefined` when it is converted into `()`, so
+/// evaluating as `()` ignores the completion value of the script rather
+/// than requiring it to be `undefined`
+fn discard_unit<T: 'static>(
+  env: &Env,
+  value: crate::Result<JsUnknown>,
+) -> crate::Result<JsUnknown> {
+  if TypeId::of::<T>() != TypeId::of::<()>() {
+    return value;
+  }
+  value.and_then(|_| {
+    env
+      .get_undefined()
+      .map(|undefined| undefined.into_unknown())
+      .map_err(|error| crate::Error::from_napi(env, error))
+  })
+}
+
+/// Runs a native callback, recording `Operation::Exec` on failure and
+/// attaching the JavaScript exception left pending by a failed napi call.
+/// Panics are returned as `Error::Panic`
+pub fn exec_result<T>(
+  env: &Env,
+  callback: impl FnOnce() -> crate::Result<T>,
+) -> crate::Result<T> {
+  catch_panic(callback).map_err(|error| match error {
+    crate::Error::NapiError(mut failure) => {
+      if failure.exception.is_none() {
+        if let Ok(Some(exception)) = env.get_and_clear_last_exception() {
+          failure.exception = Some(Box::new(js_exception_from_value(exception)));
+        }
+      }
+      crate::Error::NapiError(failure).with_operation(Operation::Exec)
+    }
+    error => error.with_operation(Operation::Exec),
+  })
+}
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+
+/// Unbounded queue that can be awaited or blocked on
+///
+/// Once closed, the values already queued can still be received
+/// after which receiving returns `None`
+pub struct Mailbox<T> {
+  state: Mutex<MailboxState<T>>,
+  condvar: Condvar,
+}
+
+struct MailboxState<T> {
+  queue: VecDeque<T>,
+  wakers: Vec<Waker>,
+  closed: bool,
+}
+
+impl<T> Default for Mailbox<T> {
+  fn default() -> Self {
+    Self {
+      state: Mutex::new(MailboxState {
+        queue: VecDeque::new(),
+        wakers: vec![],
+        closed: false,
+      }),
+      condvar: Condvar::new(),
+    }
+  }
+}
+
+impl<T> Mailbox<T> {
+  /// Returns false if the mailbox has been closed
+  pub fn push(
+    &self,
+    value: T,
+  ) -> bool {
+    let mut state = self.state.lock().unwrap();
+    if state.closed {
+      return false;
+    }
+    state.queue.push_back(value);
+    Self::notify(&mut state, &self.condvar);
+    true
+  }
+
+  pub fn close(&self) {
+    let mut state = self.state.lock().unwrap();
+    state.closed = true;
+    Self::notify(&mut state, &self.condvar);
+  }
+
+  pub fn is_closed(&self) -> bool {
+    self.state.lock().unwrap().closed
+  }
+
+  /// Block the current thread until a value is available or the mailbox is closed
+  pub fn recv(&self) -> Option<T> {
+    let mut state = self
+      .condvar
+      .wait_while(self.state.lock().unwrap(), |state| {
+        state.queue.is_empty() && !state.closed
+      })
+      .unwrap();
+    state.queue.pop_front()
+  }
+
+  pub fn try_recv(&self) -> Option<T> {
+    self.state.lock().unwrap().queue.pop_front()
+  }
+
+  pub fn poll_recv(
+    &self,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<T>> {
+    let mut state = self.state.lock().unwrap();
+
+    if let Some(value) = state.queue.pop_front() {
+      return Poll::Ready(Some(value));
+    }
+
+    if state.closed {
+      return Poll::Ready(None);
+    }
+
+    state.wakers.push(cx.waker().clone());
+    Poll::Pending
+  }
+
+  fn notify(
+    state: &mut MailboxState<T>,
+    condvar: &Condvar,
+  ) {
+    for waker in state.wakers.drain(..) {
+      waker.wake();
+    }
+    condvar.notify_all();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+  use std::thread;
+  use std::time::Duration;
+
+  use super::*;
+
+  #[test]
+  fn queued_values_are_received_after_closing() {
+    let mailbox = Mailbox::default();
+    assert!(mailbox.push(1));
+    assert!(mailbox.push(2));
+    mailbox.close();
+
+    assert!(mailbox.is_closed());
+    assert!(!mailbox.push(3));
+    assert_eq!(mailbox.recv(), Some(1));
+    assert_eq!(mailbox.try_recv(), Some(2));
+    assert_eq!(mailbox.recv(), None);
+  }
+
+  #[test]
+  fn closing_wakes_a_blocked_recv() {
+    let mailbox = Arc::new(Mailbox::<i32>::default());
+    let handle = thread::spawn({
+      let mailbox = mailbox.clone();
+      move || mailbox.recv()
+    });
+    thread::sleep(Duration::from_millis(10));
+    mailbox.close();
+    assert_eq!(handle.join().unwrap(), None);
+  }
+
+  #[test]
+  fn closing_resolves_a_pending_poll() {
+    let mailbox = Mailbox::<i32>::default();
+    let mut cx = Context::from_waker(Waker::noop());
+
+    assert!(mailbox.poll_recv(&mut cx).is_pending());
+    mailbox.close();
+    assert_eq!(mailbox.poll_recv(&mut cx), Poll::Ready(None));
+  }
+}
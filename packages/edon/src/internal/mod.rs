@@ -1,11 +1,29 @@
+mod context_sender;
 mod instance;
+mod js_exception;
+mod js_result;
+mod mailbox;
+mod modules_binding;
 mod napi_module_register;
+mod native_modules;
 mod node_embedding_main;
+mod oneshot;
 mod path_ext;
+mod request;
 mod transferrable;
+mod worker_state;
 
+pub use self::context_sender::*;
 pub use self::instance::*;
+pub use self::js_exception::*;
+pub use self::js_result::*;
+pub use self::mailbox::*;
+pub use self::modules_binding::*;
 pub use self::napi_module_register::*;
+pub use self::native_modules::*;
 pub use self::node_embedding_main::*;
+pub use self::oneshot::*;
 pub use self::path_ext::*;
+pub use self::request::*;
 pub use self::transferrable::*;
+pub use self::worker_state::*;
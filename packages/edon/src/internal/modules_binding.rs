@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::RwLock;
+
+use super::native_module;
+use crate::napi::JsUnknown;
+use crate::prelude::MODULE_HOOKS_JS;
+use crate::prelude::VIRTUAL_MODULES_JS;
+use crate::Env;
+use crate::ModuleLoader;
+use crate::ModuleSource;
+
+/// Modules served from memory, keyed by absolute path
+pub static VIRTUAL_MODULES: LazyLock<RwLock<HashMap<PathBuf, ModuleSource>>> =
+  LazyLock::new(Default::default);
+
+/// Loaders consulted by the module customization hooks, in the order they were added
+pub static MODULE_LOADERS: LazyLock<RwLock<Vec<Arc<dyn ModuleLoader>>>> =
+  LazyLock::new(Default::default);
+
+/// Registers the "edon:modules" binding the prelude uses to serve native
+/// modules, virtual modules and Rust module loaders to require() and import()
+///
+/// The binding is read from the main thread, workers and the thread
+/// running the module customization hooks
+pub fn register_modules_binding() -> crate::Result<()> {
+  super::napi_module_register("edon:modules", |env, mut exports| {
+    exports.set_named_property("install", env.create_string(VIRTUAL_MODULES_JS)?)?;
+    exports.set_named_property("hooks", env.create_string(MODULE_HOOKS_JS)?)?;
+
+    let has = env.create_function_from_closure("edon::modules::has", |ctx| {
+      let filename = ctx.get::<String>(0)?;
+      let modules = VIRTUAL_MODULES.read().unwrap();
+      Ok(modules.contains_key(Path::new(&filename)))
+    })?;
+    exports.set_named_property("has", has)?;
+
+    let get = env.create_function_from_closure("edon::modules::get", |ctx| {
+      let filename = ctx.get::<String>(0)?;
+      let modules = VIRTUAL_MODULES.read().unwrap();
+      match modules.get(Path::new(&filename)) {
+        Some(source) => create_source(ctx.env, source),
+        None => Ok(ctx.env.get_undefined()?.into_unknown()),
+      }
+    })?;
+    exports.set_named_property("get", get)?;
+
+    // (specifier, parentURL) => url | undefined
+    let resolve = env.create_function_from_closure("edon::modules::resolve", |ctx| {
+      let specifier = ctx.get::<String>(0)?;
+      let parent = ctx.get::<Option<String>>(1)?;
+
+      for loader in module_loaders() {
+        let url = loader
+          .resolve(&specifier, parent.as_deref())
+          .map_err(|error| crate::napi::Error::from_reason(error.to_string()))?;
+        if let Some(url) = url {
+          return Ok(ctx.env.create_string(&url)?.into_unknown());
+        }
+      }
+
+      Ok(ctx.env.get_undefined()?.into_unknown())
+    })?;
+    exports.set_named_property("resolve", resolve)?;
+
+    // (url) => { format, source } | undefined
+    let load = env.create_function_from_closure("edon::modules::load", |ctx| {
+      let url = ctx.get::<String>(0)?;
+
+      for loader in module_loaders() {
+        let module = loader
+          .load(&url)
+          .map_err(|error| crate::napi::Error::from_reason(error.to_string()))?;
+        if let Some(module) = module {
+          let mut result = ctx.env.create_object()?;
+          result.set_named_property("format", ctx.env.create_string(module.format.as_str())?)?;
+          result.set_named_property("source", create_source(ctx.env, &module.source)?)?;
+          return Ok(result.into_unknown());
+        }
+      }
+
+      Ok(ctx.env.get_undefined()?.into_unknown())
+    })?;
+    exports.set_named_property("load", load)?;
+
+    // (name) => version | undefined
+    let native_version =
+      env.create_function_from_closure("edon::modules::nativeVersion", |ctx| {
+        let name = ctx.get::<String>(0)?;
+        match native_module(&name) {
+          Some(module) => Ok(ctx.env.create_double(module.version as f64)?.into_unknown()),
+          None => Ok(ctx.env.get_undefined()?.into_unknown()),
+        }
+      })?;
+    exports.set_named_property("nativeVersion", native_version)?;
+
+    // (name) => { version, exports } | undefined
+    let load_native = env.create_function_from_closure("edon::modules::loadNative", |ctx| {
+      let name = ctx.get::<String>(0)?;
+      let Some(module) = native_module(&name) else {
+        return Ok(ctx.env.get_undefined()?.into_unknown());
+      };
+
+      let exports = module
+        .load(*ctx.env, ctx.env.create_object()?)
+        .map_err(|error| crate::napi::Error::from_reason(error.to_string()))?;
+
+      let mut result = ctx.env.create_object()?;
+      result.set_named_property("version", ctx.env.create_double(module.version as f64)?)?;
+      result.set_named_property("exports", exports)?;
+      Ok(result.into_unknown())
+    })?;
+    exports.set_named_property("loadNative", load_native)?;
+
+    // (name) => [...exportNames] | undefined
+    //
+    // Used by the module customization hooks, which must not create the exports
+    // themselves as that would run the module's init on the hooks thread
+    let native_export_names =
+      env.create_function_from_closure("edon::modules::nativeExportNames", |ctx| {
+        let name = ctx.get::<String>(0)?;
+        let Some(module) = native_module(&name) else {
+          return Ok(ctx.env.get_undefined()?.into_unknown());
+        };
+
+        let mut result = ctx
+          .env
+          .create_array_with_length(module.export_names.len())?;
+        for (index, export_name) in module.export_names.iter().enumerate() {
+          result.set_element(index as u32, ctx.env.create_string(export_name)?)?;
+        }
+        Ok(result.into_unknown())
+      })?;
+    exports.set_named_property("nativeExportNames", native_export_names)?;
+
+    Ok(exports)
+  })
+}
+
+/// Copy of the loaders so they can call back into the registry without deadlocking
+fn module_loaders() -> Vec<Arc<dyn ModuleLoader>> {
+  MODULE_LOADERS.read().unwrap().clone()
+}
+
+fn create_source(
+  env: &Env,
+  source: &ModuleSource,
+) -> crate::napi::Result<JsUnknown> {
+  match source {
+    ModuleSource::Text(text) => Ok(env.create_string(text)?.into_unknown()),
+    ModuleSource::Bytes(bytes) => Ok(env.create_buffer_copy(bytes)?.into_unknown()),
+  }
+}
@@ -51,8 +51,15 @@ pub fn napi_module_register<
         -> libnode_sys::napi_value {
     let env = unsafe { Env::from_raw(napi_env) };
     let exports = unsafe { JsObject::from_raw_unchecked(napi_env, napi_value) };
-    register_function(env, exports).unwrap();
-    napi_value
+    // Errors and panics are thrown from the require() or
+    // process._linkedBinding() call that loads the module
+    match crate::napi::catch_panic(|| register_function(env, exports)) {
+      Ok(_) => napi_value,
+      Err(error) => {
+        env.throw_error(&error.to_string(), None).ok();
+        ptr::null_mut()
+      }
+    }
   };
 
   let target_fn_leaked: &'static _ = Box::leak(Box::new(wrapped_fn));
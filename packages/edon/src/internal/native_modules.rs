@@ -0,0 +1,121 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::RwLock;
+
+use crate::napi::JsObject;
+use crate::napi::JsString;
+use crate::Env;
+
+pub type NativeModuleInit = Arc<dyn Fn(Env, JsObject) -> crate::Result<JsObject> + Send + Sync>;
+
+/// A native module in the registry, `version` starts at 1 and
+/// is incremented every time the module is replaced
+#[derive(Clone)]
+pub struct NativeModuleEntry {
+  pub version: u64,
+  pub init: NativeModuleInit,
+  /// Names of the exports of this version, known from the
+  /// moment the module is registered so it can be imported
+  pub export_names: Arc<[String]>,
+}
+
+impl NativeModuleEntry {
+  /// Create the exports of the module for the calling thread
+  pub fn load(
+    &self,
+    env: Env,
+    exports: JsObject,
+  ) -> crate::Result<JsObject> {
+    (self.init)(env, exports)
+  }
+}
+
+thread_local! {
+  /// Env of the JavaScript thread running on this thread, if any
+  static THREAD_ENV: Cell<Option<Env>> = const { Cell::new(None) };
+}
+
+/// Record the env of the main or worker thread running on the calling thread
+pub fn set_thread_env(env: Env) {
+  THREAD_ENV.set(Some(env));
+}
+
+/// Env of the main or worker thread running on the calling thread
+pub fn thread_env() -> Option<Env> {
+  THREAD_ENV.get()
+}
+
+/// Names of the exports created by `init`, found by running it once in `env`
+pub fn native_export_names(
+  env: Env,
+  init: &NativeModuleInit,
+) -> crate::Result<Vec<String>> {
+  let exports = init(env, env.create_object()?)?;
+  let keys = exports.get_property_names()?;
+  let mut names = vec![];
+  for index in 0..keys.get_array_length()? {
+    names.push(
+      keys
+        .get_element::<JsString>(index)?
+        .into_utf8()?
+        .into_owned()?,
+    );
+  }
+  Ok(names)
+}
+
+/// Native modules served to `require("native:<name>")` and `import("native:<name>")`
+///
+/// Unlike modules linked into libnode, which must be registered before Nodejs
+/// is initialized, these can be added and replaced while Nodejs is running.
+/// They are served to JavaScript through the `edon:modules` binding
+pub static NATIVE_MODULES: LazyLock<RwLock<HashMap<String, NativeModuleEntry>>> =
+  LazyLock::new(Default::default);
+
+/// Add a native module, fails if one with the same name exists
+pub fn register_native_module(
+  name: impl AsRef<str>,
+  init: NativeModuleInit,
+  export_names: Vec<String>,
+) -> crate::Result<()> {
+  let mut modules = NATIVE_MODULES.write().unwrap();
+  if modules.contains_key(name.as_ref()) {
+    return Err(crate::Error::NapiModuleAlreadyRegistered);
+  }
+  modules.insert(
+    name.as_ref().to_string(),
+    NativeModuleEntry {
+      version: 1,
+      init,
+      export_names: export_names.into(),
+    },
+  );
+  Ok(())
+}
+
+/// Add a native module or replace the existing one, returning its new version
+pub fn replace_native_module(
+  name: impl AsRef<str>,
+  init: NativeModuleInit,
+  export_names: Vec<String>,
+) -> u64 {
+  let mut modules = NATIVE_MODULES.write().unwrap();
+  let version = modules
+    .get(name.as_ref())
+    .map_or(1, |entry| entry.version + 1);
+  modules.insert(
+    name.as_ref().to_string(),
+    NativeModuleEntry {
+      version,
+      init,
+      export_names: export_names.into(),
+    },
+  );
+  version
+}
+
+pub fn native_module(name: &str) -> Option<NativeModuleEntry> {
+  NATIVE_MODULES.read().unwrap().get(name).cloned()
+}
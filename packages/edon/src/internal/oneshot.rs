@@ -0,0 +1,145 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+
+/// Single use channel that can be awaited or blocked on
+///
+/// This doesn't depend on any particular async runtime, the receiver
+/// is woken through the standard `Waker` it was last polled with
+pub fn oneshot<T>() -> (OneshotSender<T>, OneshotReceiver<T>) {
+  let inner = Arc::new(Inner {
+    state: Mutex::new(State {
+      value: None,
+      waker: None,
+      closed: false,
+    }),
+    condvar: Condvar::new(),
+  });
+
+  (OneshotSender(inner.clone()), OneshotReceiver(inner))
+}
+
+struct Inner<T> {
+  state: Mutex<State<T>>,
+  condvar: Condvar,
+}
+
+struct State<T> {
+  value: Option<T>,
+  waker: Option<Waker>,
+  closed: bool,
+}
+
+pub struct OneshotSender<T>(Arc<Inner<T>>);
+
+impl<T> OneshotSender<T> {
+  pub fn send(
+    self,
+    value: T,
+  ) {
+    self.0.state.lock().unwrap().value.replace(value);
+  }
+}
+
+impl<T> Drop for OneshotSender<T> {
+  fn drop(&mut self) {
+    let mut state = self.0.state.lock().unwrap();
+    state.closed = true;
+    if let Some(waker) = state.waker.take() {
+      waker.wake();
+    }
+    self.0.condvar.notify_all();
+  }
+}
+
+/// Resolves to `None` if the sender was dropped without sending a value
+pub struct OneshotReceiver<T>(Arc<Inner<T>>);
+
+impl<T> OneshotReceiver<T> {
+  /// Block the current thread until the sender sends a value or is dropped
+  pub fn recv(self) -> Option<T> {
+    let mut state = self
+      .0
+      .condvar
+      .wait_while(self.0.state.lock().unwrap(), |state| {
+        state.value.is_none() && !state.closed
+      })
+      .unwrap();
+    state.value.take()
+  }
+}
+
+impl<T> Future for OneshotReceiver<T> {
+  type Output = Option<T>;
+
+  fn poll(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    let mut state = self.0.state.lock().unwrap();
+
+    if let Some(value) = state.value.take() {
+      return Poll::Ready(Some(value));
+    }
+
+    if state.closed {
+      return Poll::Ready(None);
+    }
+
+    state.waker.replace(cx.waker().clone());
+    Poll::Pending
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::AtomicBool;
+  use std::sync::atomic::Ordering;
+  use std::task::Wake;
+  use std::thread;
+  use std::time::Duration;
+
+  use super::*;
+
+  struct FlagWaker(AtomicBool);
+
+  impl Wake for FlagWaker {
+    fn wake(self: Arc<Self>) {
+      self.0.store(true, Ordering::Release);
+    }
+  }
+
+  #[test]
+  fn recv_returns_the_sent_value() {
+    let (tx, rx) = oneshot();
+    tx.send(1);
+    assert_eq!(rx.recv(), Some(1));
+  }
+
+  #[test]
+  fn recv_returns_none_when_the_sender_is_dropped() {
+    let (tx, rx) = oneshot::<i32>();
+    let handle = thread::spawn(move || rx.recv());
+    thread::sleep(Duration::from_millis(10));
+    drop(tx);
+    assert_eq!(handle.join().unwrap(), None);
+  }
+
+  #[test]
+  fn dropping_the_sender_wakes_a_pending_poll() {
+    let (tx, mut rx) = oneshot::<i32>();
+    let flag = Arc::new(FlagWaker(AtomicBool::new(false)));
+    let waker = Waker::from(flag.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    assert!(Pin::new(&mut rx).poll(&mut cx).is_pending());
+    drop(tx);
+    assert!(flag.0.load(Ordering::Acquire));
+    assert_eq!(Pin::new(&mut rx).poll(&mut cx), Poll::Ready(None));
+  }
+}
@@ -0,0 +1,352 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+#[cfg(feature = "serde-json")]
+use serde::de::DeserializeOwned;
+
+use super::exec_result;
+use super::from_js_result;
+#[cfg(feature = "serde-json")]
+use super::from_js_result_json;
+use super::oneshot;
+use super::EvalCallback;
+use super::ExecCallback;
+use super::NodejsMainEvent;
+use super::NodejsWorkerEvent;
+use super::OneshotReceiver;
+use super::OneshotSender;
+use super::ResultCallback;
+use super::WorkerState;
+use crate::napi::bindgen_prelude::FromNapiValue;
+use crate::Env;
+use crate::EvalOptions;
+use crate::Operation;
+
+/// Requests understood by both the main thread and worker threads
+pub trait RequestEvent: Sized {
+  fn eval(
+    code: String,
+    options: &EvalOptions,
+    callback: EvalCallback,
+  ) -> Self;
+
+  fn exec(callback: ExecCallback) -> Self;
+
+  fn require(
+    specifier: String,
+    resolve: ResultCallback,
+  ) -> Self;
+
+  fn import(
+    specifier: String,
+    resolve: ResultCallback,
+  ) -> Self;
+}
+
+/// Sends requests to the JavaScript thread behind `tx`
+///
+/// `state` is the liveness of the thread when it is a worker, without
+/// it requests that can't be delivered fail with `Error::NodejsNotRunning`
+pub struct Requests<'a, E> {
+  tx: &'a Sender<E>,
+  state: Option<&'a Arc<WorkerState>>,
+}
+
+impl<'a, E: 'static + Send + RequestEvent> Requests<'a, E> {
+  pub fn new(
+    tx: &'a Sender<E>,
+    state: Option<&'a Arc<WorkerState>>,
+  ) -> Self {
+    Self { tx, state }
+  }
+
+  /// Send an event without waiting for a response
+  pub fn send(
+    &self,
+    event: E,
+  ) -> crate::Result<()> {
+    if self.state.is_some_and(|state| state.is_exited()) {
+      return Err(closed_error(self.state));
+    }
+
+    self.tx.send(event).map_err(|_| closed_error(self.state))
+  }
+
+  /// Send the event built by `event`, which responds through the sender it is given
+  pub fn request<T: 'static + Send>(
+    &self,
+    event: impl FnOnce(OneshotSender<crate::Result<T>>) -> E,
+  ) -> crate::Result<Pending<T>> {
+    let (tx, rx) = oneshot();
+    self.send(event(tx))?;
+
+    Ok(Pending {
+      rx,
+      state: self.state.cloned(),
+    })
+  }
+
+  pub fn eval<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<Pending<T>> {
+    self.request(|tx| {
+      E::eval(
+        code.as_ref().to_string(),
+        options,
+        Box::new(move |env, val| tx.send(from_js_result(&env, Operation::Eval, val))),
+      )
+    })
+  }
+
+  #[cfg(feature = "serde-json")]
+  pub fn eval_json<T: 'static + Send + DeserializeOwned>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<Pending<T>> {
+    self.request(|tx| {
+      E::eval(
+        code.as_ref().to_string(),
+        options,
+        Box::new(move |env, val| tx.send(from_js_result_json(&env, Operation::Eval, val))),
+      )
+    })
+  }
+
+  pub fn exec<R: 'static + Send>(
+    &self,
+    callback: impl 'static + Send + FnOnce(Env) -> crate::Result<R>,
+  ) -> crate::Result<Pending<R>> {
+    self.request(|tx| {
+      E::exec(Box::new(move |env| {
+        tx.send(exec_result(&env, || callback(env)));
+        Ok(())
+      }))
+    })
+  }
+
+  pub fn require(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<Pending<()>> {
+    self.request(|tx| {
+      E::require(
+        specifier.as_ref().to_string(),
+        Box::new(move |result| tx.send(result)),
+      )
+    })
+  }
+
+  pub fn import(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<Pending<()>> {
+    self.request(|tx| {
+      E::import(
+        specifier.as_ref().to_string(),
+        Box::new(move |result| tx.send(result)),
+      )
+    })
+  }
+}
+
+/// Response to a request, blocked on with `wait()` or awaited
+///
+/// Resolves to the error of the closed thread if the request is dropped without
+/// a response, see `WorkerState::closed_error` for how long that can take
+pub struct Pending<T> {
+  rx: OneshotReceiver<crate::Result<T>>,
+  state: Option<Arc<WorkerState>>,
+}
+
+impl<T> Pending<T> {
+  pub fn wait(self) -> crate::Result<T> {
+    let state = self.state;
+    self
+      .rx
+      .recv()
+      .unwrap_or_else(|| Err(closed_error(state.as_ref())))
+  }
+}
+
+impl<T> Future for Pending<T> {
+  type Output = crate::Result<T>;
+
+  fn poll(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    Pin::new(&mut self.rx)
+      .poll(cx)
+      .map(|result| result.unwrap_or_else(|| Err(closed_error(self.state.as_ref()))))
+  }
+}
+
+fn closed_error(state: Option<&Arc<WorkerState>>) -> crate::Error {
+  match state {
+    Some(state) => state.closed_error(),
+    None => crate::Error::NodejsNotRunning,
+  }
+}
+
+impl RequestEvent for NodejsMainEvent {
+  fn eval(
+    code: String,
+    options: &EvalOptions,
+    callback: EvalCallback,
+  ) -> Self {
+    let await_promise = options.await_promise;
+    let timeout = options.timeout;
+
+    if options.typescript {
+      Self::EvalTypeScript {
+        code,
+        await_promise,
+        timeout,
+        callback,
+      }
+    } else {
+      Self::Eval {
+        code,
+        await_promise,
+        timeout,
+        callback,
+      }
+    }
+  }
+
+  fn exec(callback: ExecCallback) -> Self {
+    Self::Exec { callback }
+  }
+
+  fn require(
+    specifier: String,
+    resolve: ResultCallback,
+  ) -> Self {
+    Self::Require { specifier, resolve }
+  }
+
+  fn import(
+    specifier: String,
+    resolve: ResultCallback,
+  ) -> Self {
+    Self::Import { specifier, resolve }
+  }
+}
+
+impl RequestEvent for NodejsWorkerEvent {
+  fn eval(
+    code: String,
+    options: &EvalOptions,
+    callback: EvalCallback,
+  ) -> Self {
+    let await_promise = options.await_promise;
+    let timeout = options.timeout;
+
+    if options.typescript {
+      Self::EvalTypeScript {
+        code,
+        await_promise,
+        timeout,
+        callback,
+      }
+    } else {
+      Self::Eval {
+        code,
+        await_promise,
+        timeout,
+        callback,
+      }
+    }
+  }
+
+  fn exec(callback: ExecCallback) -> Self {
+    Self::Exec { callback }
+  }
+
+  fn require(
+    specifier: String,
+    resolve: ResultCallback,
+  ) -> Self {
+    Self::Require { specifier, resolve }
+  }
+
+  fn import(
+    specifier: String,
+    resolve: ResultCallback,
+  ) -> Self {
+    Self::Import { specifier, resolve }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::mpsc::channel;
+
+  use super::*;
+  use crate::WorkerExit;
+
+  fn destroy_context() -> NodejsWorkerEvent {
+    NodejsWorkerEvent::DestroyContext { id: "id".into() }
+  }
+
+  #[test]
+  fn dropped_requests_resolve_to_the_worker_exit() {
+    let (tx, _rx) = channel();
+    let state = Arc::new(WorkerState::default());
+    let pending = Requests::new(&tx, Some(&state))
+      .request::<()>(|_resolve| destroy_context())
+      .unwrap();
+
+    state.exit(WorkerExit {
+      code: 1,
+      error: None,
+    });
+    assert!(matches!(pending.wait(), Err(crate::Error::WorkerExited)));
+  }
+
+  #[test]
+  fn awaited_requests_resolve_to_the_worker_exit() {
+    let (tx, _rx) = channel();
+    let state = Arc::new(WorkerState::default());
+    let mut pending = Requests::new(&tx, Some(&state))
+      .request::<()>(|_resolve| destroy_context())
+      .unwrap();
+    let mut cx = Context::from_waker(std::task::Waker::noop());
+
+    state.exit(WorkerExit {
+      code: 1,
+      error: Some(Box::new(crate::Error::WorkerOutOfMemory)),
+    });
+    assert!(matches!(
+      Pin::new(&mut pending).poll(&mut cx),
+      Poll::Ready(Err(crate::Error::WorkerOutOfMemory))
+    ));
+  }
+
+  #[test]
+  fn requests_to_an_exited_worker_are_not_sent() {
+    let (tx, rx) = channel();
+    let state = Arc::new(WorkerState::default());
+    state.mark_exited();
+
+    let result = Requests::new(&tx, Some(&state)).send(destroy_context());
+    assert!(matches!(result, Err(crate::Error::WorkerExited)));
+    assert!(rx.try_recv().is_err());
+  }
+
+  #[test]
+  fn requests_to_a_stopped_main_thread_fail() {
+    let (tx, rx) = channel();
+    drop(rx);
+
+    let result = Requests::new(&tx, None).request::<()>(|_resolve| destroy_context());
+    assert!(matches!(result, Err(crate::Error::NodejsNotRunning)));
+  }
+}
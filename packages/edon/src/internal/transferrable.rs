@@ -97,11 +97,12 @@ impl<T: Send + Sync + 'static> JsTransferable<T> {
     Ok(JsTransferableRef(val))
   }
 
+  #[allow(clippy::wrong_self_convention)]
   pub fn into_unknown(
     &self,
     env: &Env,
   ) -> crate::napi::Result<JsUnknown> {
-    Ok(env.create_int32(self.id.clone())?.into_unknown())
+    Ok(env.create_int32(self.id)?.into_unknown())
   }
 }
 
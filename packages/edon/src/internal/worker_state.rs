@@ -0,0 +1,165 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::WorkerExit;
+use crate::WorkerStatus;
+
+pub type ExitCallback = Box<dyn 'static + Send + FnOnce(WorkerExit)>;
+
+/// How long a failed request waits for the main thread to record why the worker
+/// exited. The exit is reported by the main thread's event loop, so it can
+/// arrive after the worker's requests have already been dropped
+const EXIT_REPORT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Liveness of a worker, shared by its handle, the thread dispatching
+/// its requests and the main thread that observes it exiting
+#[derive(Default)]
+pub struct WorkerState {
+  exited: AtomicBool,
+  exit: Mutex<Option<WorkerExit>>,
+  exit_recorded: Condvar,
+  on_exit: Mutex<Vec<ExitCallback>>,
+}
+
+impl WorkerState {
+  /// True once the worker has exited, or its requests can no longer be delivered
+  pub fn is_exited(&self) -> bool {
+    self.exited.load(Ordering::Acquire)
+  }
+
+  /// Record that requests can no longer be delivered to the worker
+  pub fn mark_exited(&self) {
+    self.exited.store(true, Ordering::Release);
+  }
+
+  /// Error for a request that could not be delivered or was dropped without a response
+  ///
+  /// Requests fail as soon as the worker stops, which can be before its exit is recorded,
+  /// so this waits up to `EXIT_REPORT_TIMEOUT` for the exit to report the reason it stopped
+  pub fn closed_error(&self) -> crate::Error {
+    let exit = self.exit.lock().unwrap();
+    let (exit, _) = self
+      .exit_recorded
+      .wait_timeout_while(exit, EXIT_REPORT_TIMEOUT, |exit| exit.is_none())
+      .unwrap();
+
+    match &*exit {
+      Some(WorkerExit {
+        error: Some(error), ..
+      }) if matches!(**error, crate::Error::WorkerOutOfMemory) => crate::Error::WorkerOutOfMemory,
+      Some(_) => crate::Error::WorkerExited,
+      None if self.is_exited() => crate::Error::WorkerExited,
+      None => crate::Error::NodejsNotRunning,
+    }
+  }
+
+  /// Record the worker's exit and notify the `on_exit` callbacks
+  pub fn exit(
+    &self,
+    exit: WorkerExit,
+  ) {
+    self.mark_exited();
+
+    // Callbacks are taken under the exit lock so none are missed by a
+    // concurrent on_exit, but are called without holding any lock
+    let callbacks = {
+      let mut current = self.exit.lock().unwrap();
+      *current = Some(exit.clone());
+      self.exit_recorded.notify_all();
+      std::mem::take(&mut *self.on_exit.lock().unwrap())
+    };
+
+    for callback in callbacks {
+      callback(exit.clone());
+    }
+  }
+
+  pub fn status(&self) -> WorkerStatus {
+    match &*self.exit.lock().unwrap() {
+      Some(exit) => WorkerStatus::Exited(exit.clone()),
+      None => WorkerStatus::Running,
+    }
+  }
+
+  /// Call `callback` when the worker exits, straight away if it already has
+  pub fn on_exit(
+    &self,
+    callback: ExitCallback,
+  ) {
+    let exit = {
+      let current = self.exit.lock().unwrap();
+      match &*current {
+        Some(exit) => exit.clone(),
+        None => {
+          self.on_exit.lock().unwrap().push(callback);
+          return;
+        }
+      }
+    };
+
+    callback(exit);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use super::*;
+
+  fn exit(code: i32) -> WorkerExit {
+    WorkerExit { code, error: None }
+  }
+
+  #[test]
+  fn exit_calls_registered_callbacks_once() {
+    let state = WorkerState::default();
+    let calls = Arc::new(Mutex::new(vec![]));
+    for _ in 0..2 {
+      let calls = calls.clone();
+      state.on_exit(Box::new(move |exit| calls.lock().unwrap().push(exit.code)));
+    }
+
+    state.exit(exit(3));
+    state.exit(exit(4));
+    assert_eq!(*calls.lock().unwrap(), vec![3, 3]);
+    assert!(state.is_exited());
+  }
+
+  #[test]
+  fn on_exit_after_exit_is_called_straight_away() {
+    let state = WorkerState::default();
+    state.exit(exit(2));
+
+    let calls = Arc::new(Mutex::new(vec![]));
+    state.on_exit(Box::new({
+      let calls = calls.clone();
+      move |exit| calls.lock().unwrap().push(exit.code)
+    }));
+    assert_eq!(*calls.lock().unwrap(), vec![2]);
+    assert!(matches!(
+      state.status(),
+      WorkerStatus::Exited(WorkerExit { code: 2, .. })
+    ));
+  }
+
+  #[test]
+  fn closed_error_reports_why_the_worker_exited() {
+    let state = WorkerState::default();
+    state.exit(WorkerExit {
+      code: 1,
+      error: Some(Box::new(crate::Error::WorkerOutOfMemory)),
+    });
+    assert!(matches!(
+      state.closed_error(),
+      crate::Error::WorkerOutOfMemory
+    ));
+
+    let state = WorkerState::default();
+    state.mark_exited();
+    assert!(matches!(state.closed_error(), crate::Error::WorkerExited));
+  }
+}
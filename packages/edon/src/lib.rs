@@ -1,18 +1,40 @@
+mod channel;
 mod error;
+mod eval_options;
+mod heap_statistics;
 pub(crate) mod internal;
+mod module_loader;
+mod module_source;
 pub mod napi;
+mod native_module;
 mod nodejs;
+mod nodejs_context;
 mod nodejs_options;
 mod nodejs_worker;
+mod nodejs_worker_pool;
+mod output_sink;
 pub(crate) mod prelude;
 mod resolve_libnode;
+mod worker_options;
 
 pub use libnode_sys as sys;
 
+pub use self::channel::*;
 pub use self::error::*;
+pub use self::eval_options::*;
+pub use self::heap_statistics::*;
+pub use self::internal::JsTransferable;
+pub use self::internal::JsTransferableRef;
+pub use self::module_loader::*;
+pub use self::module_source::*;
 pub use self::napi::js_values;
 pub use self::napi::Env;
+pub use self::native_module::*;
 pub use self::nodejs::*;
+pub use self::nodejs_context::*;
 pub use self::nodejs_options::*;
 pub use self::nodejs_worker::*;
+pub use self::nodejs_worker_pool::*;
+pub use self::output_sink::*;
 pub use self::resolve_libnode::*;
+pub use self::worker_options::*;
@@ -0,0 +1,56 @@
+use crate::ModuleSource;
+
+/// Format of a module returned by a `ModuleLoader`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleFormat {
+  /// ES Module
+  Module,
+  /// Commonjs
+  CommonJs,
+  Json,
+}
+
+impl ModuleFormat {
+  pub(crate) fn as_str(&self) -> &'static str {
+    match self {
+      ModuleFormat::Module => "module",
+      ModuleFormat::CommonJs => "commonjs",
+      ModuleFormat::Json => "json",
+    }
+  }
+}
+
+/// Module returned by `ModuleLoader::load`
+#[derive(Debug, Clone)]
+pub struct LoadedModule {
+  pub format: ModuleFormat,
+  pub source: ModuleSource,
+}
+
+/// Customizes how import() resolves and loads ES Modules
+///
+/// Loaders are consulted in the order they were added with `Nodejs::add_module_loader`,
+/// returning `None` defers to the next loader and finally to Nodejs. Returning
+/// an error rejects the import.
+///
+/// Loaders are called from the thread running Nodejs's module customization hooks
+pub trait ModuleLoader: 'static + Send + Sync {
+  /// Resolve `specifier` imported by the module at the `parent` url to a url
+  ///
+  /// The url can use a custom scheme (e.g. `edon:plugin`) as long as a loader loads it
+  fn resolve(
+    &self,
+    _specifier: &str,
+    _parent: Option<&str>,
+  ) -> crate::Result<Option<String>> {
+    Ok(None)
+  }
+
+  /// Load the source of the module at a resolved `url`
+  fn load(
+    &self,
+    _url: &str,
+  ) -> crate::Result<Option<LoadedModule>> {
+    Ok(None)
+  }
+}
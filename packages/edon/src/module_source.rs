@@ -0,0 +1,30 @@
+/// Source code of a module served from memory, see `Nodejs::add_virtual_module`
+#[derive(Debug, Clone)]
+pub enum ModuleSource {
+  Text(String),
+  Bytes(Vec<u8>),
+}
+
+impl From<String> for ModuleSource {
+  fn from(value: String) -> Self {
+    Self::Text(value)
+  }
+}
+
+impl From<&str> for ModuleSource {
+  fn from(value: &str) -> Self {
+    Self::Text(value.to_string())
+  }
+}
+
+impl From<Vec<u8>> for ModuleSource {
+  fn from(value: Vec<u8>) -> Self {
+    Self::Bytes(value)
+  }
+}
+
+impl From<&[u8]> for ModuleSource {
+  fn from(value: &[u8]) -> Self {
+    Self::Bytes(value.to_vec())
+  }
+}
@@ -0,0 +1,169 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+
+use crate::napi::bindgen_runtime::FromNapiValue;
+use crate::napi::Env;
+use crate::napi::Error;
+use crate::napi::JsExternal;
+use crate::napi::JsFunction;
+use crate::napi::JsObject;
+use crate::napi::JsUnknown;
+use crate::napi::NapiValue;
+use crate::napi::Result;
+use crate::napi::Status;
+use crate::napi::ValueType;
+
+/// The Rust side of a JavaScript `AbortSignal`
+///
+/// Accept it as an argument of a function created with `Env::create_typed_function`
+/// or `Env::create_async_function` to find out when JavaScript aborts the call.
+/// Converting the same signal more than once returns handles to the same state
+#[derive(Clone, Default)]
+pub struct AbortSignal {
+  state: Arc<Mutex<AbortState>>,
+}
+
+#[derive(Default)]
+struct AbortState {
+  aborted: bool,
+  wakers: Vec<Waker>,
+}
+
+impl AbortSignal {
+  /// Returns true once the signal has been aborted
+  pub fn aborted(&self) -> bool {
+    self.state.lock().unwrap().aborted
+  }
+
+  /// A future that completes once the signal has been aborted
+  pub fn cancelled(&self) -> Cancelled {
+    Cancelled {
+      signal: self.clone(),
+    }
+  }
+
+  fn abort(&self) {
+    let wakers = {
+      let mut state = self.state.lock().unwrap();
+      state.aborted = true;
+      std::mem::take(&mut state.wakers)
+    };
+    for waker in wakers {
+      waker.wake();
+    }
+  }
+
+  pub(crate) fn poll_aborted(
+    &self,
+    cx: &mut Context<'_>,
+  ) -> Poll<()> {
+    let mut state = self.state.lock().unwrap();
+    if state.aborted {
+      return Poll::Ready(());
+    }
+    if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+      state.wakers.push(cx.waker().clone());
+    }
+    Poll::Pending
+  }
+
+  /// Returns the handle for `value` if it is an `AbortSignal`
+  ///
+  /// The handle is stored on the signal so every conversion shares
+  /// one `abort` listener, which is added the first time
+  pub(crate) fn from_value(
+    env: &Env,
+    value: &JsUnknown,
+  ) -> Result<Option<AbortSignal>> {
+    if value.get_type()? != ValueType::Object {
+      return Ok(None);
+    }
+    let mut signal = unsafe { value.cast::<JsObject>() };
+    if signal
+      .get_named_property::<JsUnknown>("aborted")?
+      .get_type()?
+      != ValueType::Boolean
+      || signal
+        .get_named_property::<JsUnknown>("addEventListener")?
+        .get_type()?
+        != ValueType::Function
+    {
+      return Ok(None);
+    }
+
+    let existing = signal.get_property::<_, JsUnknown>(handle_key(env)?)?;
+    if existing.get_type()? == ValueType::External {
+      let existing = unsafe { existing.cast::<JsExternal>() };
+      if let Ok(handle) = env.get_value_external::<AbortSignal>(&existing) {
+        return Ok(Some(handle.clone()));
+      }
+    }
+
+    let handle = AbortSignal::default();
+    if signal.get_named_property::<bool>("aborted")? {
+      handle.abort();
+    } else {
+      let listener = {
+        let handle = handle.clone();
+        env.create_function_from_closure("onabort", move |ctx| {
+          handle.abort();
+          ctx.env.get_undefined()
+        })?
+      };
+      let mut options = env.create_object()?;
+      options.set_named_property("once", true)?;
+      signal
+        .get_named_property::<JsFunction>("addEventListener")?
+        .call(
+          Some(&signal),
+          &[
+            env.create_string("abort")?.into_unknown(),
+            listener.into_unknown(),
+            options.into_unknown(),
+          ],
+        )?;
+    }
+
+    signal.set_property(handle_key(env)?, env.create_external(handle.clone(), None)?)?;
+    Ok(Some(handle))
+  }
+}
+
+impl FromNapiValue for AbortSignal {
+  unsafe fn from_napi_value(
+    env: libnode_sys::napi_env,
+    napi_val: libnode_sys::napi_value,
+  ) -> Result<Self> {
+    let value = unsafe { JsUnknown::from_raw_unchecked(env, napi_val) };
+    AbortSignal::from_value(&unsafe { Env::from_raw(env) }, &value)?
+      .ok_or_else(|| Error::new(Status::InvalidArg, "Expected an AbortSignal".to_string()))
+  }
+}
+
+/// Future returned by `AbortSignal::cancelled`
+pub struct Cancelled {
+  signal: AbortSignal,
+}
+
+impl Future for Cancelled {
+  type Output = ();
+
+  fn poll(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<()> {
+    self.signal.poll_aborted(cx)
+  }
+}
+
+/// `Symbol.for("edon:abortSignal")`, the key the handle is stored under
+fn handle_key(env: &Env) -> Result<JsUnknown> {
+  let symbol = env.get_global()?.get_named_property::<JsObject>("Symbol")?;
+  let symbol_for = symbol.get_named_property::<JsFunction>("for")?;
+  symbol_for.call(Some(&symbol), &[env.create_string("edon:abortSignal")?])
+}
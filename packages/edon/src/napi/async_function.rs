@@ -0,0 +1,223 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use crate::napi::bindgen_runtime::FromNapiValue;
+use crate::napi::bindgen_runtime::ToNapiValue;
+use crate::napi::typed_function::get_argument;
+use crate::napi::AbortSignal;
+use crate::napi::CallContext;
+use crate::napi::Env;
+use crate::napi::Error;
+use crate::napi::JsDeferred;
+use crate::napi::JsObject;
+use crate::napi::JsUnknown;
+use crate::napi::Ref;
+use crate::napi::Result;
+use crate::napi::Status;
+
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// Runs the futures of functions created with `Env::create_async_function`
+///
+/// Implemented for closures, with tokio this can be
+/// `move |future| { handle.spawn(future); }`
+pub trait AsyncExecutor: 'static {
+  fn spawn(
+    &self,
+    future: BoxFuture<()>,
+  );
+}
+
+impl<F> AsyncExecutor for F
+where
+  F: 'static + Fn(BoxFuture<()>),
+{
+  fn spawn(
+    &self,
+    future: BoxFuture<()>,
+  ) {
+    self(future)
+  }
+}
+
+/// A Rust function that returns a future, its arguments are converted
+/// from JavaScript values and its output resolves a Promise,
+/// see `Env::create_async_function`
+///
+/// Implemented for closures taking up to 8 arguments that implement `FromNapiValue`
+/// and returning a future of a `Result` of a value that implements `ToNapiValue`
+pub trait AsyncFunction<Args>: 'static {
+  type Output: ToNapiValue + Send + 'static;
+
+  fn call(
+    &self,
+    name: &str,
+    ctx: &CallContext<'_>,
+  ) -> Result<BoxFuture<Result<Self::Output>>>;
+}
+
+macro_rules! impl_async_function {
+  ($($arg:ident),*) => {
+    impl<F, Fut, R, $($arg),*> AsyncFunction<($($arg,)*)> for F
+    where
+      F: 'static + Fn($($arg),*) -> Fut,
+      Fut: 'static + Send + Future<Output = Result<R>>,
+      R: 'static + Send + ToNapiValue,
+      $($arg: FromNapiValue,)*
+    {
+      type Output = R;
+
+      #[allow(non_snake_case, unused_mut, unused_variables)]
+      fn call(
+        &self,
+        name: &str,
+        ctx: &CallContext<'_>,
+      ) -> Result<BoxFuture<Result<R>>> {
+        let mut position = 0;
+        $(
+          position += 1;
+          let $arg = get_argument::<$arg>(name, ctx, position)?;
+        )*
+        Ok(Box::pin(self($($arg),*)))
+      }
+    }
+  };
+}
+
+impl_async_function!();
+impl_async_function!(A1);
+impl_async_function!(A1, A2);
+impl_async_function!(A1, A2, A3);
+impl_async_function!(A1, A2, A3, A4);
+impl_async_function!(A1, A2, A3, A4, A5);
+impl_async_function!(A1, A2, A3, A4, A5, A6);
+impl_async_function!(A1, A2, A3, A4, A5, A6, A7);
+impl_async_function!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+type Resolver<T> = Box<dyn FnOnce(Env) -> Result<T> + Send>;
+
+/// Calls `function` and returns a Promise settled by its future
+///
+/// The first `AbortSignal` argument cancels the call, the future is
+/// dropped and the Promise is rejected with the reason of the signal
+pub(crate) fn call_async_function<Args, E, F>(
+  name: &str,
+  executor: &E,
+  function: &F,
+  ctx: &CallContext<'_>,
+) -> Result<JsObject>
+where
+  E: AsyncExecutor,
+  F: AsyncFunction<Args>,
+{
+  let future = function.call(name, ctx)?;
+
+  let mut handle = None;
+  let mut signal = None;
+  for value in ctx.get_all() {
+    if let Some(found) = AbortSignal::from_value(ctx.env, &value)? {
+      handle = Some(found);
+      signal = Some(ctx.env.create_reference(value)?);
+      break;
+    }
+  }
+
+  let (deferred, promise) = ctx
+    .env
+    .create_deferred::<F::Output, Resolver<F::Output>>()?;
+  let mut settle = Settle {
+    name: name.to_string(),
+    deferred: Some(deferred),
+    signal,
+  };
+
+  executor.spawn(Box::pin(async move {
+    let outcome = match handle {
+      Some(handle) => Abortable { future, handle }.await,
+      None => Outcome::Done(future.await),
+    };
+    settle.settle(outcome);
+  }));
+
+  Ok(promise)
+}
+
+enum Outcome<T> {
+  Done(Result<T>),
+  Aborted,
+  Dropped,
+}
+
+/// Settles the Promise, or rejects it if the executor drops the future
+struct Settle<T: 'static + Send + ToNapiValue> {
+  name: String,
+  deferred: Option<JsDeferred<T, Resolver<T>>>,
+  signal: Option<Ref<()>>,
+}
+
+impl<T: 'static + Send + ToNapiValue> Settle<T> {
+  fn settle(
+    &mut self,
+    outcome: Outcome<T>,
+  ) {
+    let Some(deferred) = self.deferred.take() else {
+      return;
+    };
+    let name = self.name.clone();
+    let signal = self.signal.take();
+
+    deferred.resolve(Box::new(move |env| {
+      // The reference is released on the JavaScript thread whatever the outcome
+      let reason = match signal {
+        Some(mut signal) => {
+          let reason = env
+            .get_reference_value::<JsObject>(&signal)
+            .and_then(|signal| signal.get_named_property::<JsUnknown>("reason"));
+          signal.unref(env)?;
+          Some(reason?)
+        }
+        None => None,
+      };
+
+      match outcome {
+        Outcome::Done(result) => result,
+        Outcome::Aborted => Err(match reason {
+          Some(reason) => Error::from(reason),
+          None => Error::new(Status::Cancelled, format!("{} was aborted", name)),
+        }),
+        Outcome::Dropped => Err(Error::new(
+          Status::Cancelled,
+          format!("{} was dropped by its executor before completing", name),
+        )),
+      }
+    }));
+  }
+}
+
+impl<T: 'static + Send + ToNapiValue> Drop for Settle<T> {
+  fn drop(&mut self) {
+    self.settle(Outcome::Dropped);
+  }
+}
+
+/// Resolves to `Outcome::Aborted` as soon as the signal is aborted
+struct Abortable<T> {
+  future: BoxFuture<Result<T>>,
+  handle: AbortSignal,
+}
+
+impl<T> Future for Abortable<T> {
+  type Output = Outcome<T>;
+
+  fn poll(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Outcome<T>> {
+    if self.handle.poll_aborted(cx).is_ready() {
+      return Poll::Ready(Outcome::Aborted);
+    }
+    self.future.as_mut().poll(cx).map(Outcome::Done)
+  }
+}
@@ -68,7 +68,7 @@ pub fn create_iterator<T: Generator>(
       libnode_sys::napi_get_named_property(
         env,
         global,
-        "Symbol\0".as_ptr().cast(),
+        c"Symbol".as_ptr().cast(),
         &mut symbol_object,
       )
     },
@@ -81,7 +81,7 @@ pub fn create_iterator<T: Generator>(
       libnode_sys::napi_get_named_property(
         env,
         symbol_object,
-        "iterator\0".as_ptr().cast(),
+        c"iterator".as_ptr().cast(),
         &mut iterator_symbol,
       )
     },
@@ -93,7 +93,7 @@ pub fn create_iterator<T: Generator>(
     unsafe {
       libnode_sys::napi_create_function(
         env,
-        "Iterator\0".as_ptr().cast(),
+        c"Iterator".as_ptr().cast(),
         8,
         Some(symbol_generator::<T>),
         generator_ptr as *mut c_void,
@@ -144,7 +144,7 @@ pub unsafe extern "C" fn symbol_generator<T: Generator>(
     unsafe {
       libnode_sys::napi_create_function(
         env,
-        "next\0".as_ptr().cast(),
+        c"next".as_ptr().cast(),
         4,
         Some(generator_next::<T>),
         generator_ptr,
@@ -159,7 +159,7 @@ pub unsafe extern "C" fn symbol_generator<T: Generator>(
     unsafe {
       libnode_sys::napi_create_function(
         env,
-        "return\0".as_ptr().cast(),
+        c"return".as_ptr().cast(),
         6,
         Some(generator_return::<T>),
         generator_ptr,
@@ -174,7 +174,7 @@ pub unsafe extern "C" fn symbol_generator<T: Generator>(
     unsafe {
       libnode_sys::napi_create_function(
         env,
-        "throw\0".as_ptr().cast(),
+        c"throw".as_ptr().cast(),
         5,
         Some(generator_throw::<T>),
         generator_ptr,
@@ -190,7 +190,7 @@ pub unsafe extern "C" fn symbol_generator<T: Generator>(
       libnode_sys::napi_set_named_property(
         env,
         generator_object,
-        "next\0".as_ptr().cast(),
+        c"next".as_ptr().cast(),
         next_function,
       )
     },
@@ -203,7 +203,7 @@ pub unsafe extern "C" fn symbol_generator<T: Generator>(
       libnode_sys::napi_set_named_property(
         env,
         generator_object,
-        "return\0".as_ptr().cast(),
+        c"return".as_ptr().cast(),
         return_function,
       )
     },
@@ -216,7 +216,7 @@ pub unsafe extern "C" fn symbol_generator<T: Generator>(
       libnode_sys::napi_set_named_property(
         env,
         generator_object,
-        "throw\0".as_ptr().cast(),
+        c"throw".as_ptr().cast(),
         throw_function,
       )
     },
@@ -335,7 +335,7 @@ extern "C" fn generator_next<T: Generator>(
       libnode_sys::napi_set_named_property(
         env,
         result,
-        "done\0".as_ptr() as *const std::os::raw::c_char,
+        c"done".as_ptr() as *const std::os::raw::c_char,
         completed_value,
       )
     },
@@ -419,7 +419,7 @@ extern "C" fn generator_return<T: Generator>(
         libnode_sys::napi_set_named_property(
           env,
           result,
-          "value\0".as_ptr() as *const std::os::raw::c_char,
+          c"value".as_ptr() as *const std::os::raw::c_char,
           argv[0],
         )
       },
@@ -432,7 +432,7 @@ extern "C" fn generator_return<T: Generator>(
       libnode_sys::napi_set_named_property(
         env,
         result,
-        "done\0".as_ptr() as *const std::os::raw::c_char,
+        c"done".as_ptr() as *const std::os::raw::c_char,
         generator_state,
       )
     },
@@ -554,7 +554,7 @@ extern "C" fn generator_throw<T: Generator>(
   check_status_or_throw!(
     env,
     unsafe {
-      libnode_sys::napi_set_named_property(env, result, "done\0".as_ptr().cast(), generator_state)
+      libnode_sys::napi_set_named_property(env, result, c"done".as_ptr().cast(), generator_state)
     },
     "Get generator state failed"
   );
@@ -575,7 +575,7 @@ fn set_generator_value<V: ToNapiValue>(
           libnode_sys::napi_set_named_property(
             env,
             result,
-            "value\0".as_ptr() as *const std::os::raw::c_char,
+            c"value".as_ptr() as *const std::os::raw::c_char,
             val,
           )
         },
@@ -694,7 +694,7 @@ pub struct Uint8ClampedSlice<'scope> {
   raw_value: libnode_sys::napi_value,
 }
 
-impl<'scope> FromNapiValue for Uint8ClampedSlice<'scope> {
+impl FromNapiValue for Uint8ClampedSlice<'_> {
   unsafe fn from_napi_value(
     env: libnode_sys::napi_env,
     napi_val: libnode_sys::napi_value,
@@ -781,7 +781,7 @@ impl AsRef<[u8]> for Uint8ClampedSlice<'_> {
   }
 }
 
-impl<'scope> Deref for Uint8ClampedSlice<'scope> {
+impl Deref for Uint8ClampedSlice<'_> {
   type Target = [u8];
 
   fn deref(&self) -> &Self::Target {
@@ -789,7 +789,7 @@ impl<'scope> Deref for Uint8ClampedSlice<'scope> {
   }
 }
 
-impl<'scope> DerefMut for Uint8ClampedSlice<'scope> {
+impl DerefMut for Uint8ClampedSlice<'_> {
   fn deref_mut(&mut self) -> &mut Self::Target {
     self.inner
   }
@@ -34,7 +34,7 @@ pub struct BufferSlice<'scope> {
   raw_value: libnode_sys::napi_value,
 }
 
-impl<'scope> FromNapiValue for BufferSlice<'scope> {
+impl FromNapiValue for BufferSlice<'_> {
   unsafe fn from_napi_value(
     env: libnode_sys::napi_env,
     napi_val: libnode_sys::napi_value,
@@ -109,7 +109,7 @@ impl AsRef<[u8]> for BufferSlice<'_> {
   }
 }
 
-impl<'scope> Deref for BufferSlice<'scope> {
+impl Deref for BufferSlice<'_> {
   type Target = [u8];
 
   fn deref(&self) -> &Self::Target {
@@ -117,7 +117,7 @@ impl<'scope> Deref for BufferSlice<'scope> {
   }
 }
 
-impl<'scope> DerefMut for BufferSlice<'scope> {
+impl DerefMut for BufferSlice<'_> {
   fn deref_mut(&mut self) -> &mut Self::Target {
     self.inner
   }
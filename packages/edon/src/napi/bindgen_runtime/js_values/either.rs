@@ -209,13 +209,13 @@ fn silence_rejected_promise(
 ) -> crate::napi::Result<()> {
   let mut catch_method = std::ptr::null_mut();
   check_status!(unsafe {
-    libnode_sys::napi_get_named_property(env, promise, "catch\0".as_ptr().cast(), &mut catch_method)
+    libnode_sys::napi_get_named_property(env, promise, c"catch".as_ptr().cast(), &mut catch_method)
   })?;
   let mut catch_noop_callback = std::ptr::null_mut();
   check_status!(unsafe {
     libnode_sys::napi_create_function(
       env,
-      "catch\0".as_ptr().cast(),
+      c"catch".as_ptr().cast(),
       5,
       Some(noop),
       std::ptr::null_mut(),
@@ -35,9 +35,7 @@ pub struct Function<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> {
   _scope: std::marker::PhantomData<&'scope ()>,
 }
 
-impl<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> TypeName
-  for Function<'scope, Args, Return>
-{
+impl<Args: JsValuesTupleIntoVec, Return: FromNapiValue> TypeName for Function<'_, Args, Return> {
   fn type_name() -> &'static str {
     "Function"
   }
@@ -47,16 +45,14 @@ impl<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> TypeName
   }
 }
 
-impl<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> NapiRaw
-  for Function<'scope, Args, Return>
-{
+impl<Args: JsValuesTupleIntoVec, Return: FromNapiValue> NapiRaw for Function<'_, Args, Return> {
   unsafe fn raw(&self) -> libnode_sys::napi_value {
     self.value
   }
 }
 
-impl<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> FromNapiValue
-  for Function<'scope, Args, Return>
+impl<Args: JsValuesTupleIntoVec, Return: FromNapiValue> FromNapiValue
+  for Function<'_, Args, Return>
 {
   unsafe fn from_napi_value(
     env: libnode_sys::napi_env,
@@ -72,12 +68,12 @@ impl<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> FromNapiValue
   }
 }
 
-impl<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> ValidateNapiValue
-  for Function<'scope, Args, Return>
+impl<Args: JsValuesTupleIntoVec, Return: FromNapiValue> ValidateNapiValue
+  for Function<'_, Args, Return>
 {
 }
 
-impl<'scope, Args: JsValuesTupleIntoVec, Return: FromNapiValue> Function<'scope, Args, Return> {
+impl<Args: JsValuesTupleIntoVec, Return: FromNapiValue> Function<'_, Args, Return> {
   /// Call the JavaScript function.
   /// `this` in the JavaScript function will be `undefined`.
   /// If you want to specify `this`, you can use the `apply` method.
@@ -150,12 +150,7 @@ impl ToNapiValue for Number {
     } else {
       let n = n.as_u64().unwrap();
       if n > u32::MAX as u64 {
-        {
-          return unsafe { BigInt::to_napi_value(env, BigInt::from(n)) };
-        }
-
-        #[cfg(not(feature = "napi6"))]
-        return unsafe { String::to_napi_value(env, n.to_string()) };
+        unsafe { BigInt::to_napi_value(env, BigInt::from(n)) }
       } else {
         unsafe { u32::to_napi_value(env, n as u32) }
       }
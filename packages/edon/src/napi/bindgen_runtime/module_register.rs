@@ -156,7 +156,7 @@ pub fn register_class(
 
 #[inline]
 /// Get `JsFunction` from defined Rust `fn`
-/// ```rust
+/// ```rust,ignore
 /// #[napi]
 /// fn some_fn() -> u32 {
 ///     1
@@ -210,7 +210,7 @@ pub fn get_js_function(
 }
 
 /// Get `C Callback` from defined Rust `fn`
-/// ```rust
+/// ```rust,ignore
 /// #[napi]
 /// fn some_fn() -> u32 {
 ///     1
@@ -16,10 +16,12 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::napi::async_cleanup_hook::AsyncCleanupHook;
+use crate::napi::async_function::call_async_function;
 use crate::napi::async_work::AsyncWorkPromise;
 use crate::napi::async_work::{self};
 use crate::napi::bindgen_runtime::FromNapiValue;
 use crate::napi::bindgen_runtime::ToNapiValue;
+use crate::napi::catch_panic;
 use crate::napi::check_status;
 use crate::napi::cleanup_env::CleanupEnvHook;
 use crate::napi::cleanup_env::CleanupEnvHookData;
@@ -31,12 +33,15 @@ use crate::napi::js_values::*;
 use crate::napi::task::Task;
 use crate::napi::threadsafe_function::ThreadSafeCallContext;
 use crate::napi::threadsafe_function::ThreadsafeFunction;
+use crate::napi::AsyncExecutor;
+use crate::napi::AsyncFunction;
 use crate::napi::Error;
 use crate::napi::ExtendedErrorInfo;
 use crate::napi::JsError;
 use crate::napi::NodeVersion;
 use crate::napi::Result;
 use crate::napi::Status;
+use crate::napi::TypedFunction;
 use crate::napi::ValueType;
 
 pub type Callback = unsafe extern "C" fn(
@@ -750,6 +755,69 @@ impl Env {
     Ok(unsafe { JsFunction::from_raw_unchecked(self.0, raw_result) })
   }
 
+  /// Create a function from a Rust closure with typed arguments and return value
+  ///
+  /// Arguments are converted with `FromNapiValue` and the return value with
+  /// `ToNapiValue`. Missing arguments are converted from `undefined`, so they
+  /// can be taken as `Option<T>`. An argument that cannot be converted throws
+  /// a `TypeError` naming its position, errors returned by the closure are thrown as `Error`
+  ///
+  /// ```ignore
+  /// env.create_typed_function("repeat", |text: String, times: u32| {
+  ///   Ok(text.repeat(times as usize))
+  /// })?;
+  /// ```
+  pub fn create_typed_function<Args, F>(
+    &self,
+    name: &str,
+    callback: F,
+  ) -> Result<JsFunction>
+  where
+    F: TypedFunction<Args>,
+  {
+    let function_name = name.to_string();
+    self.create_function_from_closure(name, move |ctx| callback.call(&function_name, &ctx))
+  }
+
+  /// Create a function from a Rust closure that returns a future, the function returns a Promise
+  ///
+  /// Arguments are converted like `create_typed_function`. The future is passed to `executor`,
+  /// which runs it on the runtime of your choice, and its output resolves the Promise. Errors
+  /// reject the Promise, as does the executor dropping the future before it completes
+  ///
+  /// Passing an `AbortSignal` cancels the call, once it is aborted the future is dropped
+  /// and the Promise is rejected with the reason of the signal. Take it as an `AbortSignal`
+  /// argument to check for cancellation from within the future
+  ///
+  /// ```ignore
+  /// let handle = runtime.handle().clone();
+  /// env.create_async_function(
+  ///   "sleep",
+  ///   move |future| {
+  ///     handle.spawn(future);
+  ///   },
+  ///   |ms: u32, _signal: Option<AbortSignal>| async move {
+  ///     tokio::time::sleep(Duration::from_millis(ms as u64)).await;
+  ///     Ok(ms)
+  ///   },
+  /// )?;
+  /// ```
+  pub fn create_async_function<Args, E, F>(
+    &self,
+    name: &str,
+    executor: E,
+    callback: F,
+  ) -> Result<JsFunction>
+  where
+    E: AsyncExecutor,
+    F: AsyncFunction<Args>,
+  {
+    let function_name = name.to_string();
+    self.create_function_from_closure(name, move |ctx| {
+      call_async_function(&function_name, &executor, &callback, &ctx)
+    })
+  }
+
   /// This API retrieves a napi_extended_error_info structure with information about the last error that occurred.
   ///
   /// The content of the napi_extended_error_info returned is only valid up until an n-api function is called on the same env.
@@ -765,6 +833,27 @@ impl Env {
     unsafe { ptr::read(raw_extended_error) }.try_into()
   }
 
+  /// Returns true if a JavaScript exception is pending on this env
+  pub fn is_exception_pending(&self) -> Result<bool> {
+    let mut is_pending = false;
+    check_status!(unsafe { libnode_sys::napi_is_exception_pending(self.0, &mut is_pending) })?;
+    Ok(is_pending)
+  }
+
+  /// Returns and clears the pending JavaScript exception, if there is one
+  pub fn get_and_clear_last_exception(&self) -> Result<Option<JsUnknown>> {
+    if !self.is_exception_pending()? {
+      return Ok(None);
+    }
+    let mut raw_exception = ptr::null_mut();
+    check_status!(unsafe {
+      libnode_sys::napi_get_and_clear_last_exception(self.0, &mut raw_exception)
+    })?;
+    Ok(Some(unsafe {
+      JsUnknown::from_raw_unchecked(self.0, raw_exception)
+    }))
+  }
+
   /// Throw any JavaScript value
   pub fn throw<T: NapiRaw>(
     &self,
@@ -1678,7 +1767,7 @@ pub(crate) unsafe extern "C" fn trampoline<
   let closure: &F = Box::leak(unsafe { Box::from_raw(closure_data_ptr.cast()) });
   let mut env = unsafe { Env::from_raw(raw_env) };
   let call_context = CallContext::new(&mut env, cb_info, raw_this, raw_args.as_slice(), argc);
-  closure(call_context)
+  catch_panic(|| closure(call_context))
     .and_then(|ret: R| unsafe { <R as ToNapiValue>::to_napi_value(env.0, ret) })
     .unwrap_or_else(|e| {
       unsafe { JsError::from(e).throw_into(raw_env) };
@@ -1728,11 +1817,13 @@ pub(crate) unsafe extern "C" fn trampoline_setter<
     .ok_or_else(|| Error::new(Status::InvalidArg, "Missing argument in property setter"))
     .and_then(|value| unsafe { V::from_napi_value(raw_env, *value) })
     .and_then(|value| {
-      closure(
-        env,
-        unsafe { Object::from_raw_unchecked(raw_env, raw_this) },
-        value,
-      )
+      catch_panic(|| {
+        closure(
+          env,
+          unsafe { Object::from_raw_unchecked(raw_env, raw_this) },
+          value,
+        )
+      })
     })
     .map(|_| std::ptr::null_mut())
     .unwrap_or_else(|e| {
@@ -1773,8 +1864,10 @@ pub(crate) unsafe extern "C" fn trampoline_getter<
 
   let closure: &F = Box::leak(unsafe { Box::from_raw(closure_data_ptr.cast()) });
   let env = unsafe { Env::from_raw(raw_env) };
-  closure(env, unsafe {
-    crate::napi::bindgen_runtime::Object::from_raw_unchecked(raw_env, raw_this)
+  catch_panic(|| {
+    closure(env, unsafe {
+      crate::napi::bindgen_runtime::Object::from_raw_unchecked(raw_env, raw_this)
+    })
   })
   .and_then(|ret: R| unsafe { <R as ToNapiValue>::to_napi_value(env.0, ret) })
   .unwrap_or_else(|e| {
@@ -1,11 +1,11 @@
+use std::any::Any;
 use std::convert::From;
 use std::convert::TryFrom;
 use std::error;
-use std::ffi::CString;
+use std::ffi::CStr;
 use std::fmt;
 #[cfg(feature = "serde-json")]
 use std::fmt::Display;
-use std::os::raw::c_char;
 use std::os::raw::c_void;
 use std::ptr;
 
@@ -193,6 +193,37 @@ impl From<std::io::Error> for Error {
   }
 }
 
+/// Errors a panic caught by `catch_panic` is converted into
+pub(crate) trait FromPanic {
+  fn from_panic(message: String) -> Self;
+}
+
+impl FromPanic for Error {
+  fn from_panic(message: String) -> Self {
+    Error::new(Status::GenericFailure, format!("Rust panic: {}", message))
+  }
+}
+
+/// Runs `f`, converting a panic into an error so it does not
+/// unwind across an `extern "C"` boundary
+pub(crate) fn catch_panic<T, E: FromPanic>(
+  f: impl FnOnce() -> std::result::Result<T, E>
+) -> std::result::Result<T, E> {
+  std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+    .unwrap_or_else(|payload| Err(E::from_panic(panic_message(&*payload))))
+}
+
+/// Message of a panic payload, as passed to `panic!`
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+  if let Some(message) = payload.downcast_ref::<&str>() {
+    message.to_string()
+  } else if let Some(message) = payload.downcast_ref::<String>() {
+    message.clone()
+  } else {
+    "Box<dyn Any>".to_string()
+  }
+}
+
 #[derive(Clone, Debug)]
 pub struct ExtendedErrorInfo {
   pub message: String,
@@ -206,10 +237,13 @@ impl TryFrom<libnode_sys::napi_extended_error_info> for ExtendedErrorInfo {
 
   fn try_from(value: libnode_sys::napi_extended_error_info) -> Result<Self> {
     Ok(Self {
-      message: unsafe {
-        CString::from_raw(value.error_message as *mut c_char)
-          .into_string()
-          .map_err(|e| Error::new(Status::GenericFailure, format!("{e}")))?
+      // The message is owned by napi and may be null when there is no error
+      message: if value.error_message.is_null() {
+        String::new()
+      } else {
+        unsafe { CStr::from_ptr(value.error_message) }
+          .to_string_lossy()
+          .into_owned()
       },
       engine_error_code: value.engine_error_code,
       engine_reserved: value.engine_reserved,
@@ -7,6 +7,7 @@ use serde::de::SeqAccess;
 use serde::de::Unexpected;
 use serde::de::VariantAccess;
 use serde::de::Visitor;
+use serde::forward_to_deserialize_any;
 
 use crate::napi::bindgen_runtime::FromNapiValue;
 use crate::napi::type_of;
@@ -31,7 +32,7 @@ impl<'env> De<'env> {
 }
 
 #[doc(hidden)]
-impl<'x, 'de, 'env> serde::de::Deserializer<'x> for &'de mut De<'env> {
+impl<'x> serde::de::Deserializer<'x> for &mut De<'_> {
   type Error = Error;
 
   fn deserialize_any<V>(
@@ -235,7 +236,7 @@ pub(crate) struct JsVariantAccess<'env> {
 }
 
 #[doc(hidden)]
-impl<'de, 'env> VariantAccess<'de> for JsVariantAccess<'env> {
+impl<'de> VariantAccess<'de> for JsVariantAccess<'_> {
   type Error = Error;
   fn unit_variant(self) -> Result<()> {
     match self.value {
@@ -341,7 +342,7 @@ impl<'env> JsArrayAccess<'env> {
 }
 
 #[doc(hidden)]
-impl<'de, 'env> SeqAccess<'de> for JsArrayAccess<'env> {
+impl<'de> SeqAccess<'de> for JsArrayAccess<'_> {
   type Error = Error;
 
   fn next_element_seed<T>(
@@ -385,7 +386,7 @@ impl<'env> JsObjectAccess<'env> {
 }
 
 #[doc(hidden)]
-impl<'de, 'env> MapAccess<'de> for JsObjectAccess<'env> {
+impl<'de> MapAccess<'de> for JsObjectAccess<'_> {
   type Error = Error;
 
   fn next_key_seed<K>(
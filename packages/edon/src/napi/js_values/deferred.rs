@@ -5,6 +5,7 @@ use std::ptr;
 use libnode_sys;
 
 use crate::napi::bindgen_runtime::ToNapiValue;
+use crate::napi::catch_panic;
 use crate::napi::check_status;
 use crate::napi::Env;
 use crate::napi::Error;
@@ -119,7 +120,7 @@ fn js_deferred_new_raw(
     unsafe {
       libnode_sys::napi_create_string_utf8(
         env,
-        "napi_resolve_deferred\0".as_ptr().cast(),
+        c"napi_resolve_deferred".as_ptr().cast(),
         22,
         &mut async_resource_name,
       )
@@ -166,7 +167,7 @@ extern "C" fn napi_resolve_deferred<Data: ToNapiValue, Resolver: FnOnce(Env) ->
   let deferred_data: Box<DeferredData<Data, Resolver>> = unsafe { Box::from_raw(data.cast()) };
   let result = deferred_data
     .resolver
-    .and_then(|resolver| resolver(unsafe { Env::from_raw(env) }))
+    .and_then(|resolver| catch_panic(|| resolver(unsafe { Env::from_raw(env) })))
     .and_then(|res| unsafe { ToNapiValue::to_napi_value(env, res) });
 
   if let Err(e) = result.and_then(|res| {
@@ -175,9 +176,9 @@ extern "C" fn napi_resolve_deferred<Data: ToNapiValue, Resolver: FnOnce(Env) ->
       "Resolve deferred value failed"
     )
   }) {
-    let error = Ok::<libnode_sys::napi_value, Error>(unsafe {
-      crate::napi::JsError::from(e).into_value(env)
-    });
+    // Errors created from a JavaScript value, such as the reason of an
+    // AbortSignal, reject with that value even if it is not an Error
+    let error = unsafe { ToNapiValue::to_napi_value(env, e) };
 
     match error {
       Ok(error) => {
@@ -191,7 +192,7 @@ extern "C" fn napi_resolve_deferred<Data: ToNapiValue, Resolver: FnOnce(Env) ->
           unsafe {
             libnode_sys::napi_create_string_utf8(
               env,
-              "Rejection failed\0".as_ptr().cast(),
+              c"Rejection failed".as_ptr().cast(),
               0,
               &mut err_msg,
             );
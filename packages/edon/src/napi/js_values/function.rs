@@ -34,7 +34,7 @@ impl TypeName for JsFunction {
 /// See [Working with JavaScript Functions](https://nodejs.org/api/n-api.html#n_api_working_with_javascript_functions).
 ///
 /// Example:
-/// ```
+/// ```rust,ignore
 /// use napi::{JsFunction, CallContext, JsNull, Result};
 ///
 /// #[js_function(1)]
@@ -148,7 +148,7 @@ impl JsFunction {
       libnode_sys::napi_get_named_property(
         self.0.env,
         self.0.value,
-        "name\0".as_ptr().cast(),
+        c"name".as_ptr().cast(),
         &mut name,
       )
     })?;
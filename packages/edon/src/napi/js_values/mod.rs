@@ -35,6 +35,7 @@ mod global;
 mod number;
 mod object;
 mod object_property;
+mod promise;
 mod string;
 mod tagged_object;
 mod undefined;
@@ -55,6 +56,7 @@ pub use global::*;
 pub use number::JsNumber;
 pub use object::*;
 pub use object_property::*;
+pub use promise::JsPromise;
 #[cfg(feature = "serde-json")]
 pub use ser::Ser;
 pub use string::*;
@@ -782,6 +784,7 @@ impl_js_value_methods!(JsString);
 impl_js_value_methods!(JsObject);
 impl_js_value_methods!(JsGlobal);
 impl_js_value_methods!(JsDate);
+impl_js_value_methods!(JsPromise);
 impl_js_value_methods!(JsFunction);
 impl_js_value_methods!(JsExternal);
 impl_js_value_methods!(JsSymbol);
@@ -794,6 +797,7 @@ impl_object_methods!(JsArrayBuffer);
 impl_object_methods!(JsTypedArray);
 impl_object_methods!(JsDataView);
 impl_object_methods!(JsGlobal);
+impl_object_methods!(JsPromise);
 impl_object_methods!(JSON);
 
 use ValueType::*;
@@ -810,6 +814,7 @@ impl_napi_value_trait!(JsString, String);
 impl_napi_value_trait!(JsObject, Object);
 impl_napi_value_trait!(JsGlobal, Object);
 impl_napi_value_trait!(JsDate, Object);
+impl_napi_value_trait!(JsPromise, Object);
 impl_napi_value_trait!(JsTimeout, Object);
 impl_napi_value_trait!(JsFunction, Function);
 impl_napi_value_trait!(JsExternal, External);
@@ -0,0 +1,168 @@
+use std::cell::Cell;
+use std::future::Future;
+use std::ptr;
+use std::rc::Rc;
+
+use libnode_sys;
+
+use super::check_status;
+use crate::internal::js_exception_from_value;
+use crate::internal::oneshot;
+use crate::napi::bindgen_runtime::FromNapiValue;
+use crate::napi::bindgen_runtime::ToNapiValue;
+use crate::napi::bindgen_runtime::TypeName;
+use crate::napi::bindgen_runtime::ValidateNapiValue;
+use crate::napi::Env;
+use crate::napi::Error;
+use crate::napi::JsFunction;
+use crate::napi::JsObject;
+use crate::napi::JsUnknown;
+use crate::napi::NapiRaw;
+use crate::napi::NapiValue;
+use crate::napi::Result;
+use crate::napi::Status;
+use crate::napi::Value;
+use crate::napi::ValueType;
+
+/// A JavaScript Promise
+///
+/// Continuations can be attached with `then`/`catch` or the promise can be
+/// converted into a Rust future that resolves once the promise settles
+pub struct JsPromise(pub(crate) Value);
+
+impl TypeName for JsPromise {
+  fn type_name() -> &'static str {
+    "Promise"
+  }
+
+  fn value_type() -> crate::napi::ValueType {
+    ValueType::Object
+  }
+}
+
+impl ValidateNapiValue for JsPromise {
+  unsafe fn validate(
+    env: libnode_sys::napi_env,
+    napi_val: libnode_sys::napi_value,
+  ) -> Result<libnode_sys::napi_value> {
+    let mut is_promise = false;
+    check_status!(unsafe { libnode_sys::napi_is_promise(env, napi_val, &mut is_promise) })?;
+    if !is_promise {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Expected a Promise object".to_owned(),
+      ));
+    }
+
+    Ok(ptr::null_mut())
+  }
+}
+
+impl JsPromise {
+  /// Attach a callback that runs on the JavaScript thread when the promise is fulfilled
+  ///
+  /// The returned promise resolves to the value returned by the callback
+  pub fn then<R, F>(
+    &self,
+    on_fulfilled: F,
+  ) -> Result<JsPromise>
+  where
+    R: ToNapiValue,
+    F: 'static + FnOnce(Env, JsUnknown) -> Result<R>,
+  {
+    let on_fulfilled = self.create_continuation("JsPromise::then", on_fulfilled)?;
+    self.call_method("then", &[on_fulfilled])
+  }
+
+  /// Attach a callback that runs on the JavaScript thread when the promise is rejected
+  ///
+  /// The returned promise resolves to the value returned by the callback
+  pub fn catch<R, F>(
+    &self,
+    on_rejected: F,
+  ) -> Result<JsPromise>
+  where
+    R: ToNapiValue,
+    F: 'static + FnOnce(Env, JsUnknown) -> Result<R>,
+  {
+    let on_rejected = self.create_continuation("JsPromise::catch", on_rejected)?;
+    self.call_method("catch", &[on_rejected])
+  }
+
+  /// Convert the promise into a Rust future
+  ///
+  /// The resolved value is converted into a Rust value on the JavaScript thread.
+  /// A rejection is returned as a `crate::Error::JsException`
+  pub fn into_future<T: 'static + Send + FromNapiValue>(
+    self
+  ) -> Result<impl Future<Output = crate::Result<T>> + Send> {
+    let (tx, rx) = oneshot();
+    let tx = Rc::new(Cell::new(Some(tx)));
+
+    let on_fulfilled = self.create_continuation("JsPromise::resolve", {
+      let tx = tx.clone();
+      move |_env, value| {
+        if let Some(tx) = tx.take() {
+          tx.send(T::from_unknown(value).map_err(crate::Error::from));
+        }
+        Ok(())
+      }
+    })?;
+
+    let on_rejected = self.create_continuation("JsPromise::reject", move |_env, error| {
+      if let Some(tx) = tx.take() {
+        tx.send(Err(js_exception_from_value(error)));
+      }
+      Ok(())
+    })?;
+
+    self.call_method("then", &[on_fulfilled, on_rejected])?;
+
+    Ok(async move {
+      rx.await.unwrap_or_else(|| {
+        Err(crate::Error::generic(
+          "Promise was dropped before it settled",
+        ))
+      })
+    })
+  }
+
+  fn create_continuation<R, F>(
+    &self,
+    name: &str,
+    callback: F,
+  ) -> Result<JsFunction>
+  where
+    R: ToNapiValue,
+    F: 'static + FnOnce(Env, JsUnknown) -> Result<R>,
+  {
+    let env = unsafe { Env::from_raw(self.0.env) };
+    let callback = Cell::new(Some(callback));
+
+    env.create_function_from_closure(name, move |ctx| {
+      let value = if ctx.length > 0 {
+        ctx.get::<JsUnknown>(0)?
+      } else {
+        ctx.env.get_undefined()?.into_unknown()
+      };
+      let Some(callback) = callback.take() else {
+        return Err(Error::new(
+          Status::GenericFailure,
+          "Promise continuation called more than once".to_owned(),
+        ));
+      };
+      callback(*ctx.env, value)
+    })
+  }
+
+  fn call_method(
+    &self,
+    method: &str,
+    args: &[JsFunction],
+  ) -> Result<JsPromise> {
+    let this = unsafe { JsObject::from_raw_unchecked(self.0.env, self.0.value) };
+    let method = this.get_named_property::<JsFunction>(method)?;
+    let promise = method.call(Some(&this), args)?;
+    Ok(unsafe { JsPromise::from_raw_unchecked(self.0.env, promise.raw()) })
+  }
+}
@@ -1,7 +1,9 @@
 #![allow(non_upper_case_globals)]
 
+mod abort_signal;
 mod async_cleanup_hook;
 pub use async_cleanup_hook::AsyncCleanupHook;
+mod async_function;
 mod async_work;
 mod bindgen_runtime;
 mod call_context;
@@ -12,11 +14,18 @@ pub mod js_values;
 mod status;
 mod task;
 pub mod threadsafe_function;
+mod typed_function;
 mod value_type;
 mod version;
 
 pub use cleanup_env::CleanupEnvHook;
 
+pub use self::abort_signal::AbortSignal;
+pub use self::abort_signal::Cancelled;
+pub(crate) use self::async_function::call_async_function;
+pub use self::async_function::AsyncExecutor;
+pub use self::async_function::AsyncFunction;
+pub use self::async_function::BoxFuture;
 pub use self::async_work::AsyncWorkPromise;
 pub use self::bindgen_runtime::iterator;
 pub use self::bindgen_runtime::*;
@@ -26,13 +35,10 @@ pub use self::error::*;
 pub use self::js_values::*;
 pub use self::status::Status;
 pub use self::task::Task;
+pub use self::typed_function::TypedFunction;
 pub use self::value_type::*;
 pub use self::version::NodeVersion;
 
-#[cfg(feature = "serde-json")]
-#[macro_use]
-extern crate serde;
-
 pub type ContextlessResult<T> = Result<Option<T>>;
 
 #[doc(hidden)]
@@ -41,7 +47,7 @@ macro_rules! _type_of {
   ($env:expr, $value:expr) => {{
     let mut value_type = 0;
     #[allow(unused_unsafe)]
-    crate::napi::error::check_status!(unsafe {
+    $crate::napi::error::check_status!(unsafe {
       libnode_sys::napi_typeof($env, $value, &mut value_type)
     })
     .and_then(|_| Ok($crate::napi::ValueType::from(value_type)))
@@ -21,6 +21,7 @@ use crate::napi::bindgen_runtime::JsValuesTupleIntoVec;
 use crate::napi::bindgen_runtime::ToNapiValue;
 use crate::napi::bindgen_runtime::TypeName;
 use crate::napi::bindgen_runtime::ValidateNapiValue;
+use crate::napi::catch_panic;
 use crate::napi::check_status;
 use crate::napi::Env;
 use crate::napi::JsError;
@@ -215,7 +216,7 @@ struct ThreadsafeFunctionCallJsBackData<T> {
 /// ## Example
 /// An example of using `ThreadsafeFunction`:
 ///
-/// ```rust
+/// ```rust,ignore
 /// #[macro_use]
 /// extern crate napi_derive;
 ///
@@ -548,6 +549,36 @@ impl<T: 'static> ThreadsafeFunction<T, ErrorStrategy::Fatal> {
     })
   }
 
+  /// Like `call`, but hands the value back if it could not be queued
+  /// (e.g. the function is closing) rather than dropping or leaking it
+  pub fn try_call(
+    &self,
+    value: T,
+    mode: ThreadsafeFunctionCallMode,
+  ) -> std::result::Result<(), T> {
+    self.handle.with_read_aborted(|aborted| {
+      if aborted {
+        return Err(value);
+      }
+
+      let data = Box::into_raw(Box::new(ThreadsafeFunctionCallJsBackData {
+        data: value,
+        call_variant: ThreadsafeFunctionCallVariant::Direct,
+        callback: Box::new(|_d: Result<JsUnknown>| Ok(())),
+      }));
+      let status = unsafe {
+        libnode_sys::napi_call_threadsafe_function(self.handle.get_raw(), data.cast(), mode.into())
+      };
+
+      if status == libnode_sys::Status::napi_ok {
+        Ok(())
+      } else {
+        // The data is only owned by napi when the call succeeds
+        Err(unsafe { Box::from_raw(data) }.data)
+      }
+    })
+  }
+
   pub fn call_with_return_value<D: FromNapiValue, F: 'static + FnOnce(D) -> Result<()>>(
     &self,
     value: T,
@@ -631,9 +662,11 @@ unsafe extern "C" fn call_js_cb<T: 'static, V: ToNapiValue, R, ES>(
   unsafe { libnode_sys::napi_get_undefined(raw_env, &mut recv) };
 
   let ret = val.and_then(|v| {
-    (ctx)(ThreadSafeCallContext {
-      env: unsafe { Env::from_raw(raw_env) },
-      value: v.data,
+    catch_panic(|| {
+      (ctx)(ThreadSafeCallContext {
+        env: unsafe { Env::from_raw(raw_env) },
+        value: v.data,
+      })
     })
     .map(|ret| (ret, v.call_variant, v.callback))
   });
@@ -703,7 +736,7 @@ unsafe extern "C" fn call_js_cb<T: 'static, V: ToNapiValue, R, ES>(
             value_type: crate::napi::ValueType::Unknown,
           }))
         };
-        if let Err(err) = callback(callback_arg) {
+        if let Err(err) = catch_panic(|| callback(callback_arg)) {
           let message = format!(
             "Failed to convert return value in ThreadsafeFunction callback into Rust value: {err}"
           );
@@ -711,7 +744,7 @@ unsafe extern "C" fn call_js_cb<T: 'static, V: ToNapiValue, R, ES>(
           let c_message = CString::new(message).unwrap();
           unsafe {
             libnode_sys::napi_fatal_error(
-              "threadsafe_function.rs:749\0".as_ptr().cast(),
+              c"threadsafe_function.rs:749".as_ptr().cast(),
               26,
               c_message.as_ptr(),
               message_length as isize,
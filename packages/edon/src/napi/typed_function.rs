@@ -0,0 +1,87 @@
+use crate::napi::bindgen_runtime::FromNapiValue;
+use crate::napi::bindgen_runtime::ToNapiValue;
+use crate::napi::CallContext;
+use crate::napi::Error;
+use crate::napi::JsTypeError;
+use crate::napi::JsUnknown;
+use crate::napi::NapiRaw;
+use crate::napi::Result;
+use crate::napi::Status;
+
+/// A Rust function whose arguments and return value are converted
+/// from and to JavaScript values, see `Env::create_typed_function`
+///
+/// Implemented for closures taking up to 8 arguments that implement
+/// `FromNapiValue` and returning a `Result` of a value that implements `ToNapiValue`
+pub trait TypedFunction<Args>: 'static {
+  type Output: ToNapiValue;
+
+  fn call(
+    &self,
+    name: &str,
+    ctx: &CallContext<'_>,
+  ) -> Result<Self::Output>;
+}
+
+macro_rules! impl_typed_function {
+  ($($arg:ident),*) => {
+    impl<F, R, $($arg),*> TypedFunction<($($arg,)*)> for F
+    where
+      F: 'static + Fn($($arg),*) -> Result<R>,
+      R: ToNapiValue,
+      $($arg: FromNapiValue,)*
+    {
+      type Output = R;
+
+      #[allow(non_snake_case, unused_mut, unused_variables)]
+      fn call(
+        &self,
+        name: &str,
+        ctx: &CallContext<'_>,
+      ) -> Result<R> {
+        let mut position = 0;
+        $(
+          position += 1;
+          let $arg = get_argument::<$arg>(name, ctx, position)?;
+        )*
+        self($($arg),*)
+      }
+    }
+  };
+}
+
+impl_typed_function!();
+impl_typed_function!(A1);
+impl_typed_function!(A1, A2);
+impl_typed_function!(A1, A2, A3);
+impl_typed_function!(A1, A2, A3, A4);
+impl_typed_function!(A1, A2, A3, A4, A5);
+impl_typed_function!(A1, A2, A3, A4, A5, A6);
+impl_typed_function!(A1, A2, A3, A4, A5, A6, A7);
+impl_typed_function!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+/// Converts the argument at `position` (starting at 1), missing arguments are
+/// converted from `undefined`. Throws a `TypeError` when the conversion fails
+pub(crate) fn get_argument<T: FromNapiValue>(
+  name: &str,
+  ctx: &CallContext<'_>,
+  position: usize,
+) -> Result<T> {
+  let value = if position <= ctx.length {
+    unsafe { ctx.get::<JsUnknown>(position - 1)?.raw() }
+  } else {
+    unsafe { ctx.env.get_undefined()?.raw() }
+  };
+
+  unsafe { T::from_napi_value(ctx.env.raw(), value) }.map_err(|error| {
+    let message = format!(
+      "Invalid argument {} passed to {}: {}",
+      position, name, error.reason
+    );
+    unsafe {
+      JsTypeError::from(Error::new(Status::InvalidArg, message.clone())).throw_into(ctx.env.raw())
+    };
+    // The TypeError is already pending, so the trampoline leaves it in place
+    Error::new(Status::PendingException, message)
+  })
+}
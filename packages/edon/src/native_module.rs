@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::internal::NativeModuleInit;
+use crate::napi::bindgen_prelude::ToNapiValue;
+use crate::napi::call_async_function;
+use crate::napi::AsyncExecutor;
+use crate::napi::AsyncFunction;
+use crate::napi::Callback;
+use crate::napi::JsUnknown;
+use crate::napi::NapiValue;
+use crate::napi::Property;
+use crate::napi::TypedFunction;
+use crate::Env;
+
+/// Creates the value of an export, once for every thread that loads the module
+type CreateExport = Arc<dyn Fn(&Env) -> crate::napi::Result<JsUnknown> + Send + Sync>;
+
+/// A native module declared with `NativeModule::builder`
+///
+/// Register it with `Nodejs::register_native_module`, it is then
+/// available in JavaScript via `require("native:<name>")`
+pub struct NativeModule {
+  name: String,
+  exports: Vec<(String, CreateExport)>,
+}
+
+impl NativeModule {
+  /// Start declaring a native module called `name`
+  pub fn builder(name: impl AsRef<str>) -> NativeModuleBuilder {
+    NativeModuleBuilder::new(name.as_ref().to_string())
+  }
+
+  /// Name the module is registered under
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// The name, the names of the exports and the function that creates the exports for the registry
+  pub(crate) fn into_parts(self) -> (String, Vec<String>, NativeModuleInit) {
+    let exports = self.exports;
+    let export_names = exports.iter().map(|(name, _)| name.clone()).collect();
+
+    let init: NativeModuleInit = Arc::new(move |env, mut object| {
+      for (name, create) in &exports {
+        object.set_named_property(name, create(&env)?)?;
+      }
+      Ok(object)
+    });
+
+    (self.name, export_names, init)
+  }
+}
+
+/// Collects the exports of a native module or of one of its namespaces
+///
+/// Names are validated when the module is built, exporting
+/// the same name twice from a namespace is an error
+pub struct NativeModuleBuilder {
+  path: String,
+  names: HashSet<String>,
+  duplicates: Vec<String>,
+  exports: Vec<(String, CreateExport)>,
+}
+
+impl NativeModuleBuilder {
+  fn new(path: String) -> Self {
+    Self {
+      path,
+      names: Default::default(),
+      duplicates: Default::default(),
+      exports: Default::default(),
+    }
+  }
+
+  /// Export a function with typed arguments, see `Env::create_typed_function`
+  pub fn function<Args, F>(
+    self,
+    name: impl AsRef<str>,
+    callback: F,
+  ) -> Self
+  where
+    F: TypedFunction<Args> + Send + Sync,
+  {
+    let name = name.as_ref().to_string();
+    let qualified_name = format!("{}.{}", self.path, name);
+    let callback = Arc::new(callback);
+
+    self.export(name.clone(), move |env| {
+      let callback = callback.clone();
+      let qualified_name = qualified_name.clone();
+      let function =
+        env.create_function_from_closure(&name, move |ctx| callback.call(&qualified_name, &ctx))?;
+      Ok(function.into_unknown())
+    })
+  }
+
+  /// Export a function that returns a Promise, see `Env::create_async_function`
+  pub fn async_function<Args, E, F>(
+    self,
+    name: impl AsRef<str>,
+    executor: E,
+    callback: F,
+  ) -> Self
+  where
+    E: AsyncExecutor + Send + Sync,
+    F: AsyncFunction<Args> + Send + Sync,
+  {
+    let name = name.as_ref().to_string();
+    let qualified_name = format!("{}.{}", self.path, name);
+    let executor = Arc::new(executor);
+    let callback = Arc::new(callback);
+
+    self.export(name.clone(), move |env| {
+      let executor = executor.clone();
+      let callback = callback.clone();
+      let qualified_name = qualified_name.clone();
+      let function = env.create_function_from_closure(&name, move |ctx| {
+        call_async_function(&qualified_name, &*executor, &*callback, &ctx)
+      })?;
+      Ok(function.into_unknown())
+    })
+  }
+
+  /// Export a constant value
+  pub fn constant<T>(
+    self,
+    name: impl AsRef<str>,
+    value: T,
+  ) -> Self
+  where
+    T: 'static + Send + Sync + Clone + ToNapiValue,
+  {
+    self.export(name, move |env| {
+      let value = unsafe { T::to_napi_value(env.raw(), value.clone())? };
+      Ok(unsafe { JsUnknown::from_raw_unchecked(env.raw(), value) })
+    })
+  }
+
+  /// Export a class defined with `Env::define_class`
+  ///
+  /// `properties` is called for every thread that loads the module
+  pub fn class(
+    self,
+    name: impl AsRef<str>,
+    constructor: Callback,
+    properties: impl 'static + Send + Sync + Fn(&Env) -> crate::napi::Result<Vec<Property>>,
+  ) -> Self {
+    let name = name.as_ref().to_string();
+
+    self.export(name.clone(), move |env| {
+      let class = env.define_class(&name, constructor, &properties(env)?)?;
+      Ok(class.into_unknown())
+    })
+  }
+
+  /// Export an object holding the exports declared by `declare`
+  pub fn namespace(
+    mut self,
+    name: impl AsRef<str>,
+    declare: impl FnOnce(NativeModuleBuilder) -> NativeModuleBuilder,
+  ) -> Self {
+    let name = name.as_ref().to_string();
+    let namespace = declare(NativeModuleBuilder::new(format!("{}.{}", self.path, name)));
+
+    self.duplicates.extend(namespace.duplicates);
+    let exports = namespace.exports;
+
+    self.export(name, move |env| {
+      let mut object = env.create_object()?;
+      for (name, create) in &exports {
+        object.set_named_property(name, create(env)?)?;
+      }
+      Ok(object.into_unknown())
+    })
+  }
+
+  /// Export a value created by `create`, for values
+  /// that are not covered by the other methods
+  pub fn export(
+    mut self,
+    name: impl AsRef<str>,
+    create: impl 'static + Send + Sync + Fn(&Env) -> crate::napi::Result<JsUnknown>,
+  ) -> Self {
+    let name = name.as_ref().to_string();
+
+    if !self.names.insert(name.clone()) {
+      self.duplicates.push(format!("{}.{}", self.path, name));
+    }
+
+    self.exports.push((name, Arc::new(create)));
+    self
+  }
+
+  /// Validate the declared exports
+  pub fn build(self) -> crate::Result<NativeModule> {
+    if !self.duplicates.is_empty() {
+      return Err(crate::Error::generic(format!(
+        "Native module exports the same name more than once: {}",
+        self.duplicates.join(", ")
+      )));
+    }
+
+    Ok(NativeModule {
+      name: self.path,
+      exports: self.exports,
+    })
+  }
+}
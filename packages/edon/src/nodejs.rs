@@ -1,36 +1,67 @@
 use std::path::Path;
-use std::sync::atomic::AtomicU32;
-use std::sync::atomic::Ordering;
 use std::sync::mpsc::channel;
 use std::sync::mpsc::Sender;
-use std::sync::OnceLock;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[cfg(feature = "serde-json")]
+use serde::de::DeserializeOwned;
 
 use super::internal;
 use super::NodejsWorker;
+use crate::internal::ContextSender;
+use crate::internal::NativeModuleInit;
 use crate::internal::NodejsMainEvent;
+use crate::internal::RequestEvent;
+use crate::internal::Requests;
+use crate::napi::bindgen_prelude::FromNapiValue;
 use crate::napi::JsObject;
 use crate::napi::JsUnknown;
+use crate::Channel;
+use crate::ChannelValue;
 use crate::Env;
+use crate::EvalOptions;
+use crate::HeapStatistics;
+use crate::HeapStatisticsSampler;
+use crate::ModuleLoader;
+use crate::ModuleSource;
+use crate::NativeModule;
 use crate::NodejsOptions;
+use crate::NodejsWorkerPool;
+use crate::NodejsWorkerPoolOptions;
+use crate::WorkerOptions;
+use crate::DEFAULT_WORKER_STOP_TIMEOUT;
 
 // Due to a quirk of v8, only one instance of Nodejs can be used per process.
 // The current C FFI does not allow spawning multiple contexts so to get around
 // this for now, we store the Nodejs instance as a static and inject
 // a JavaScript prelude that creates "vm" instances to act as contexts.
 //
-// The consumer can also spawn and interact with Nodejs worker threads.
-static NODEJS: OnceLock<crate::Result<NodejsRef>> = OnceLock::new();
-pub(crate) static NODEJS_CONTEXT_COUNT: AtomicU32 = AtomicU32::new(0);
+// The consumer can also spawn and interact with Nodejs worker threads,
+// each of which can host any number of isolated "vm" contexts.
+//
+// v8 cannot be initialized again once it has been torn down, so Nodejs is
+// started by the first load and keeps running until Nodejs::shutdown(),
+// handles can be dropped and loaded again freely in between.
+static RUNTIME: Mutex<Runtime> = Mutex::new(Runtime::NotStarted);
 
 pub type NodejsRef = Sender<NodejsMainEvent>;
 
+enum Runtime {
+  NotStarted,
+  Running(NodejsRef),
+  Failed(crate::Error),
+  ShutDown,
+}
+
 pub struct Nodejs {
   tx_main: NodejsRef,
 }
 
 impl Nodejs {
   /// Load libnode by path
-  /// ```
+  /// ```text
   /// Windows:  "libnode.dll"
   /// MacOS:    "libnode.dylib"
   /// Linux:    "libnode.so"
@@ -39,24 +70,32 @@ impl Nodejs {
     path: P,
     args: &[Args],
   ) -> crate::Result<Nodejs> {
-    NODEJS_CONTEXT_COUNT.fetch_add(1, Ordering::AcqRel);
-
-    let nodejs = NODEJS.get_or_init(move || {
-      let _ = libnode_sys::load::cdylib(path);
-      let tx_main = internal::start_node_instance(args)?;
-      Ok(tx_main)
-    });
-
-    match nodejs {
-      Ok(nodejs) => Ok(Self {
-        tx_main: nodejs.clone(),
+    let mut runtime = RUNTIME.lock().unwrap();
+
+    match &*runtime {
+      Runtime::NotStarted => {
+        let _ = libnode_sys::load::cdylib(path);
+        match internal::start_node_instance(args) {
+          Ok(tx_main) => {
+            *runtime = Runtime::Running(tx_main.clone());
+            Ok(Self { tx_main })
+          }
+          Err(error) => {
+            *runtime = Runtime::Failed(error.clone());
+            Err(error)
+          }
+        }
+      }
+      Runtime::Running(tx_main) => Ok(Self {
+        tx_main: tx_main.clone(),
       }),
-      Err(err) => Err(err.clone()),
+      Runtime::Failed(error) => Err(error.clone()),
+      Runtime::ShutDown => Err(crate::Error::NodejsShutDown),
     }
   }
 
   /// Load libnode by path
-  /// ```
+  /// ```text
   /// Windows:  "libnode.dll"
   /// MacOS:    "libnode.dylib"
   /// Linux:    "libnode.so"
@@ -66,7 +105,7 @@ impl Nodejs {
   }
 
   /// Load libnode by path
-  /// ```
+  /// ```text
   /// Windows:  "libnode.dll"
   /// MacOS:    "libnode.dylib"
   /// Linux:    "libnode.so"
@@ -75,10 +114,59 @@ impl Nodejs {
     Self::load_with_args(options.libnode_path.clone(), &options.as_argv())
   }
 
+  /// Returns true once Nodejs has been loaded and until it is shut down
+  pub fn is_running() -> bool {
+    matches!(*RUNTIME.lock().unwrap(), Runtime::Running(_))
+  }
+
+  /// Stop Nodejs, waiting for its workers to shut down
+  ///
+  /// Workers are given `DEFAULT_WORKER_STOP_TIMEOUT` to shut down cleanly, see `shutdown_with_timeout`.
+  /// Handles that are still alive return `Error::NodejsNotRunning` afterwards.
+  /// Shutting down is terminal, libnode cannot be initialized twice in the same
+  /// process so loading Nodejs again afterwards returns `Error::NodejsShutDown`.
+  ///
+  /// Dropping handles never shuts Nodejs down, call this explicitly
+  pub fn shutdown() -> crate::Result<()> {
+    Self::shutdown_with_timeout(DEFAULT_WORKER_STOP_TIMEOUT)
+  }
+
+  /// Stop Nodejs, waiting up to `timeout` for each of its workers to shut down cleanly
+  ///
+  /// Workers are stopped concurrently. Those that don't shut down in time
+  /// (e.g. stuck in an infinite loop) have their execution terminated
+  pub fn shutdown_with_timeout(timeout: Duration) -> crate::Result<()> {
+    let tx_main = {
+      let mut runtime = RUNTIME.lock().unwrap();
+      let Runtime::Running(tx_main) = &*runtime else {
+        return Err(crate::Error::NodejsNotRunning);
+      };
+      let tx_main = tx_main.clone();
+      *runtime = Runtime::ShutDown;
+      tx_main
+    };
+
+    let (tx, rx) = channel();
+    tx_main
+      .send(NodejsMainEvent::StopMain {
+        timeout,
+        resolve: tx,
+      })
+      .map_err(|_| crate::Error::NodejsNotRunning)?;
+    rx.recv().map_err(|_| crate::Error::NodejsNotRunning)
+  }
+
   /// Register native module
   ///
-  /// This runs once per main/worker thread and is accessible
-  /// in JavaScript via `importNative("my_native_extension")`
+  /// This runs once per main/worker thread and is accessible in JavaScript
+  /// via `require("native:my_native_extension")` or `import("native:my_native_extension")`.
+  /// Modules can be registered at any time, threads load them on first use.
+  /// They are not linked into libnode, so `process._linkedBinding()` can't load them
+  ///
+  /// `import()` provides the properties of the exports as named exports. To learn
+  /// their names the module is created once when it is registered, on the calling
+  /// thread if it is a main/worker thread and on the main thread otherwise.
+  /// Use `NativeModule::builder` to declare them up front instead
   pub fn napi_module_register<
     S: AsRef<str>,
     F: 'static + Sync + Send + Fn(Env, JsObject) -> crate::Result<JsObject>,
@@ -87,136 +175,310 @@ impl Nodejs {
     module_name: S,
     register_function: F,
   ) -> crate::Result<()> {
-    internal::napi_module_register(module_name, register_function)
+    if internal::native_module(module_name.as_ref()).is_some() {
+      return Err(crate::Error::NapiModuleAlreadyRegistered);
+    }
+    let init: NativeModuleInit = Arc::new(register_function);
+    let export_names = self.native_export_names(&init)?;
+    internal::register_native_module(module_name, init, export_names)
+  }
+
+  /// Register a native module or replace the one registered under the same name,
+  /// returning its version. The first registration is version 1
+  ///
+  /// Threads that already loaded the module keep the exports they have, `require()`
+  /// and `import()` calls made after the replacement load the new version.
+  /// Like `napi_module_register`, the module is created once to learn its export names
+  pub fn napi_module_replace<
+    S: AsRef<str>,
+    F: 'static + Sync + Send + Fn(Env, JsObject) -> crate::Result<JsObject>,
+  >(
+    &self,
+    module_name: S,
+    register_function: F,
+  ) -> crate::Result<u64> {
+    let init: NativeModuleInit = Arc::new(register_function);
+    let export_names = self.native_export_names(&init)?;
+    Ok(internal::replace_native_module(
+      module_name,
+      init,
+      export_names,
+    ))
+  }
+
+  /// Version of the native module registered under `module_name`
+  pub fn napi_module_version(
+    &self,
+    module_name: impl AsRef<str>,
+  ) -> Option<u64> {
+    internal::native_module(module_name.as_ref()).map(|module| module.version)
+  }
+
+  /// Register a native module declared with `NativeModule::builder`
+  ///
+  /// Like `napi_module_register`, the exports are created once per main/worker thread
+  pub fn register_native_module(
+    &self,
+    module: NativeModule,
+  ) -> crate::Result<()> {
+    let (name, export_names, init) = module.into_parts();
+    internal::register_native_module(name, init, export_names)
+  }
+
+  /// Register a native module declared with `NativeModule::builder`, or replace
+  /// the one registered under the same name, see `napi_module_replace`
+  pub fn replace_native_module(
+    &self,
+    module: NativeModule,
+  ) -> u64 {
+    let (name, export_names, init) = module.into_parts();
+    internal::replace_native_module(name, init, export_names)
   }
 
   /// Spawn a Nodejs worker thread
   pub fn spawn_worker_thread(&self) -> crate::Result<NodejsWorker> {
-    self.spawn_worker_thread_with_options(&NodejsOptions::default())
+    self.spawn_worker_thread_with_options(&WorkerOptions::default())
   }
 
+  /// Spawn a Nodejs worker thread with its own arguments, environment and resource limits
   pub fn spawn_worker_thread_with_options(
     &self,
-    options: &NodejsOptions,
+    options: &WorkerOptions,
   ) -> crate::Result<NodejsWorker> {
     NodejsWorker::start(options, self.tx_main.clone())
   }
 
-  pub fn eval<Code: AsRef<str>>(
+  /// Spawn a pool of Nodejs worker threads that jobs are load balanced across
+  pub fn spawn_worker_pool(
     &self,
-    code: Code,
-    callback: impl 'static + Send + FnOnce(Env, JsUnknown),
+    options: &NodejsWorkerPoolOptions,
+  ) -> crate::Result<NodejsWorkerPool> {
+    NodejsWorkerPool::start(options, |options| {
+      self.spawn_worker_thread_with_options(options)
+    })
+  }
+
+  /// Serve a module from memory at `path` to require() and import()
+  ///
+  /// `path` must be absolute and does not need to exist on disk. The module format
+  /// is taken from the extension: `.mjs` is ESM, `.json` is JSON and anything else
+  /// is Commonjs. Modules are cached once loaded so replacing the source only
+  /// affects contexts that haven't loaded it yet
+  pub fn add_virtual_module(
+    &self,
+    path: impl AsRef<Path>,
+    source: impl Into<ModuleSource>,
   ) -> crate::Result<()> {
-    self
-      .tx_main
-      .send(NodejsMainEvent::Eval {
-        code: code.as_ref().to_string(),
-        callback: Box::new(callback),
-      })
-      .unwrap();
+    let path = path.as_ref();
+    if !path.is_absolute() {
+      return Err(crate::Error::generic(format!(
+        "Virtual module path must be absolute: {}",
+        path.display()
+      )));
+    }
+
+    internal::VIRTUAL_MODULES
+      .write()
+      .unwrap()
+      .insert(path.to_path_buf(), source.into());
 
     Ok(())
   }
 
-  /// Evaluate Block of Commonjs JavaScript
+  /// Stop serving a module added with `add_virtual_module`
+  pub fn remove_virtual_module(
+    &self,
+    path: impl AsRef<Path>,
+  ) -> bool {
+    internal::VIRTUAL_MODULES
+      .write()
+      .unwrap()
+      .remove(path.as_ref())
+      .is_some()
+  }
+
+  /// Add a loader that customizes how import() resolves and loads ES Modules
   ///
-  /// The last line of the script will be returned
-  pub fn eval_blocking<Code: AsRef<str>>(
+  /// Loaders are consulted in the order they were added, before virtual modules
+  /// and Nodejs's own resolution
+  pub fn add_module_loader(
+    &self,
+    loader: impl ModuleLoader,
+  ) {
+    internal::MODULE_LOADERS
+      .write()
+      .unwrap()
+      .push(Arc::new(loader));
+  }
+
+  /// Open a two way message channel with JavaScript
+  ///
+  /// The JavaScript end is assigned to `globalThis[name]`
+  pub fn open_channel<In: ChannelValue, Out: ChannelValue>(
+    &self,
+    name: impl AsRef<str>,
+  ) -> crate::Result<Channel<In, Out>> {
+    Channel::open(ContextSender::Main(self.tx_main.clone()), name)
+  }
+
+  pub fn eval<Code: AsRef<str>>(
     &self,
     code: Code,
+    callback: impl 'static + Send + FnOnce(Env, crate::Result<JsUnknown>),
   ) -> crate::Result<()> {
-    let (tx, rx) = channel();
+    self.requests().send(NodejsMainEvent::eval(
+      code.as_ref().to_string(),
+      &Default::default(),
+      Box::new(callback),
+    ))
+  }
 
-    self
-      .tx_main
-      .send(NodejsMainEvent::Eval {
-        code: code.as_ref().to_string(),
-        callback: Box::new(move |_env, _val| {
-          tx.send(Ok(())).unwrap();
-        }),
-      })
-      .ok();
+  /// Evaluate Block of Commonjs JavaScript
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread. Evaluate as `()` to ignore the value
+  pub fn eval_blocking<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, &Default::default())?.wait()
+  }
 
-    rx.recv().unwrap()
+  /// Evaluate Block of Commonjs JavaScript
+  ///
+  /// The last line of the script will be returned, deserialized
+  /// into a Rust value on the JavaScript thread
+  #[cfg(feature = "serde-json")]
+  pub fn eval_json<T: 'static + Send + DeserializeOwned>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.requests().eval_json(code, &Default::default())?.wait()
   }
 
   /// Evaluate Block of ESM JavaScript
   pub fn eval_typescript<Code: AsRef<str>>(
     &self,
     code: Code,
-    callback: impl 'static + Send + FnOnce(Env, JsUnknown),
+    callback: impl 'static + Send + FnOnce(Env, crate::Result<JsUnknown>),
   ) -> crate::Result<()> {
-    self
-      .tx_main
-      .send(NodejsMainEvent::EvalTypeScript {
-        code: code.as_ref().to_string(),
-        callback: Box::new(callback),
-      })
-      .unwrap();
-
-    Ok(())
+    self.requests().send(NodejsMainEvent::eval(
+      code.as_ref().to_string(),
+      &EvalOptions {
+        typescript: true,
+        ..Default::default()
+      },
+      Box::new(callback),
+    ))
   }
 
   /// Evaluate Block of ESM JavaScript
-  pub fn eval_typescript_blocking<Code: AsRef<str>>(
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub fn eval_typescript_blocking<T: 'static + Send + FromNapiValue>(
     &self,
-    code: Code,
-  ) -> crate::Result<()> {
-    let (tx, rx) = channel();
-
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
     self
-      .tx_main
-      .send(NodejsMainEvent::EvalTypeScript {
-        code: code.as_ref().to_string(),
-        callback: Box::new(move |_env, _val| {
-          tx.send(Ok(())).unwrap();
-        }),
-      })
-      .ok();
+      .requests()
+      .eval(
+        code,
+        &EvalOptions {
+          typescript: true,
+          ..Default::default()
+        },
+      )?
+      .wait()
+  }
 
-    rx.recv().unwrap()
+  /// Evaluate Block of JavaScript with options
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub fn eval_with_options_blocking<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, options)?.wait()
+  }
+
+  /// Evaluate Block of JavaScript with options without blocking the current thread
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub async fn eval_with_options_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, options)?.await
+  }
+
+  /// Evaluate Block of Commonjs JavaScript, interrupting it if it runs for longer than `timeout`
+  ///
+  /// Returns `Error::Timeout` when interrupted. The context remains usable
+  /// though changes made by the script before it was interrupted are kept
+  pub fn eval_with_timeout<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    timeout: Duration,
+  ) -> crate::Result<T> {
+    self.eval_with_options_blocking(
+      code,
+      &EvalOptions {
+        timeout: Some(timeout),
+        ..Default::default()
+      },
+    )
   }
 
   /// Evaluate Native JavaScript
   ///
   /// This will provide a Nodejs Env and allow execution of
   /// native code in the JavaScript context
-  pub fn exec_blocking<F: 'static + Send + FnOnce(Env) -> crate::Result<()>>(
+  pub fn exec_blocking<R: 'static + Send, F: 'static + Send + FnOnce(Env) -> crate::Result<R>>(
     &self,
     callback: F,
-  ) -> crate::Result<()> {
-    let (tx, rx) = channel();
-
-    self
-      .tx_main
-      .send(NodejsMainEvent::Exec {
-        callback: Box::new(move |env| {
-          let result = callback(env);
-          tx.send(Ok(())).unwrap();
-          result
-        }),
-      })
-      .ok();
-
-    rx.recv().unwrap()
+  ) -> crate::Result<R> {
+    self.requests().exec(callback)?.wait()
   }
 
   /// Evaluate Native JavaScript
   ///
   /// This will provide a Nodejs Env and allow execution of
-  /// native code in the JavaScript context
+  /// native code in the JavaScript context.
+  /// Errors and panics of `callback` are printed to stderr,
+  /// use `exec_blocking` to receive them instead
   pub fn exec<F: 'static + Send + FnOnce(Env) -> crate::Result<()>>(
     &self,
     callback: F,
   ) -> crate::Result<()> {
     self
-      .tx_main
-      .send(NodejsMainEvent::Exec {
-        callback: Box::new(callback),
-      })
-      .unwrap();
-    
-    Ok(())
+      .requests()
+      .send(NodejsMainEvent::exec(Box::new(callback)))
+  }
+
+  /// Collect the heap statistics of the main Nodejs thread
+  pub fn heap_statistics(&self) -> crate::Result<HeapStatistics> {
+    self.exec_blocking(|env| HeapStatistics::collect(&env))
+  }
+
+  /// Call `callback` with the heap statistics of the main Nodejs thread
+  /// every `interval` until the returned sampler is dropped
+  pub fn sample_heap_statistics(
+    &self,
+    interval: Duration,
+    callback: impl 'static + Send + FnMut(crate::Result<HeapStatistics>),
+  ) -> HeapStatisticsSampler {
+    let context = ContextSender::Main(self.tx_main.clone());
+    HeapStatisticsSampler::start(
+      interval,
+      move || context.exec_blocking(|env| HeapStatistics::collect(&env)),
+      || !Nodejs::is_running(),
+      callback,
+    )
   }
 
   /// Call Nodejs's require() function to import code
@@ -224,17 +486,7 @@ impl Nodejs {
     &self,
     specifier: Specifier,
   ) -> crate::Result<()> {
-    let (tx, rx) = channel();
-
-    self
-      .tx_main
-      .send(NodejsMainEvent::Require {
-        specifier: specifier.as_ref().to_string(),
-        resolve: tx,
-      })
-      .ok();
-
-    rx.recv().unwrap()
+    self.requests().require(specifier)?.wait()
   }
 
   /// Call Nodejs's await import() to import code
@@ -242,30 +494,89 @@ impl Nodejs {
     &self,
     specifier: Specifier,
   ) -> crate::Result<()> {
-    let (tx, rx) = channel();
+    self.requests().import(specifier)?.wait()
+  }
 
+  /// Evaluate Block of Commonjs JavaScript without blocking the current thread
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub async fn eval_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, &Default::default())?.await
+  }
+
+  /// Evaluate Block of ESM JavaScript without blocking the current thread
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub async fn eval_typescript_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
     self
-      .tx_main
-      .send(NodejsMainEvent::Import {
-        specifier: specifier.as_ref().to_string(),
-        resolve: tx,
-      })
-      .ok();
+      .requests()
+      .eval(
+        code,
+        &EvalOptions {
+          typescript: true,
+          ..Default::default()
+        },
+      )?
+      .await
+  }
 
-    rx.recv().unwrap()
+  /// Evaluate Native JavaScript without blocking the current thread
+  ///
+  /// This will provide a Nodejs Env and allow execution of
+  /// native code in the JavaScript context
+  pub async fn exec_async<
+    R: 'static + Send,
+    F: 'static + Send + FnOnce(Env) -> crate::Result<R>,
+  >(
+    &self,
+    callback: F,
+  ) -> crate::Result<R> {
+    self.requests().exec(callback)?.await
+  }
+
+  /// Call Nodejs's require() function to import code without
+  /// blocking the current thread
+  pub async fn require_async(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.requests().require(specifier)?.await
+  }
+
+  /// Call Nodejs's await import() to import code without
+  /// blocking the current thread
+  pub async fn import_async(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.requests().import(specifier)?.await
   }
-}
 
-impl Drop for Nodejs {
-  fn drop(&mut self) {
-    let context_count = NODEJS_CONTEXT_COUNT.fetch_sub(1, Ordering::AcqRel);
-    if context_count == 1 {
-      let (tx, rx) = channel();
-      self
-        .tx_main
-        .send(NodejsMainEvent::StopMain { resolve: tx })
-        .unwrap();
-      rx.recv().unwrap();
+  fn requests(&self) -> Requests<'_, NodejsMainEvent> {
+    Requests::new(&self.tx_main, None)
+  }
+
+  /// Names of the exports of a native module, created in the env of the calling
+  /// thread when it runs JavaScript so it never waits on itself
+  fn native_export_names(
+    &self,
+    init: &NativeModuleInit,
+  ) -> crate::Result<Vec<String>> {
+    if let Some(env) = internal::thread_env() {
+      return internal::native_export_names(env, init);
     }
+    let init = init.clone();
+    self
+      .requests()
+      .exec(move |env| internal::native_export_names(env, &init))?
+      .wait()
   }
 }
@@ -0,0 +1,370 @@
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "serde-json")]
+use serde::de::DeserializeOwned;
+
+use crate::internal::exec_result;
+use crate::internal::from_js_result;
+#[cfg(feature = "serde-json")]
+use crate::internal::from_js_result_json;
+use crate::internal::NodejsWorkerEvent;
+use crate::internal::Pending;
+use crate::internal::Requests;
+use crate::internal::WorkerState;
+use crate::napi::bindgen_prelude::FromNapiValue;
+use crate::napi::JsObject;
+use crate::napi::JsUnknown;
+use crate::Env;
+use crate::EvalOptions;
+use crate::Operation;
+
+/// Options for a vm context created within a Nodejs worker
+#[derive(Debug, Clone)]
+pub struct NodejsContextOptions {
+  /// Globals of the worker that are exposed to the context.
+  ///
+  /// `"require"` is special cased and exposes a `require()` that
+  /// evaluates Commonjs modules inside the context
+  pub globals: Vec<String>,
+}
+
+impl Default for NodejsContextOptions {
+  fn default() -> Self {
+    Self {
+      globals: [
+        "console",
+        "setTimeout",
+        "clearTimeout",
+        "setInterval",
+        "clearInterval",
+        "setImmediate",
+        "clearImmediate",
+        "queueMicrotask",
+        "structuredClone",
+        "URL",
+        "URLSearchParams",
+        "TextEncoder",
+        "TextDecoder",
+        "require",
+      ]
+      .into_iter()
+      .map(String::from)
+      .collect(),
+    }
+  }
+}
+
+/// An isolated JavaScript context within a Nodejs worker, backed by `vm.createContext`
+///
+/// Each context has its own `globalThis` and Commonjs module cache but shares the
+/// thread, event loop and builtin modules of its worker. This makes them much cheaper
+/// than a worker per tenant, though they are not a security boundary.
+///
+/// Dropping the context releases it
+pub struct NodejsContext {
+  id: String,
+  tx_wrk: Sender<NodejsWorkerEvent>,
+  state: Arc<WorkerState>,
+}
+
+impl NodejsContext {
+  pub(crate) fn start(
+    options: &NodejsContextOptions,
+    tx_wrk: Sender<NodejsWorkerEvent>,
+    state: Arc<WorkerState>,
+  ) -> crate::Result<Self> {
+    let id = Requests::new(&tx_wrk, Some(&state))
+      .request(|tx| NodejsWorkerEvent::CreateContext {
+        globals: options.globals.clone(),
+        callback: Box::new(move |_env, val| {
+          tx.send(val.and_then(|val| Ok(String::from_unknown(val)?)));
+        }),
+      })?
+      .wait()?;
+
+    Ok(Self { id, tx_wrk, state })
+  }
+
+  pub fn eval<Code: AsRef<str>>(
+    &self,
+    code: Code,
+    callback: impl 'static + Send + FnOnce(Env, crate::Result<JsUnknown>),
+  ) -> crate::Result<()> {
+    self
+      .requests()
+      .send(self.eval_event(code, &Default::default(), callback))
+  }
+
+  /// Evaluate Block of JavaScript in the context
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread. Evaluate as `()` to ignore the value
+  pub fn eval_blocking<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.eval_with_options_blocking(code, &Default::default())
+  }
+
+  /// Evaluate Block of JavaScript in the context
+  ///
+  /// The last line of the script will be returned, deserialized
+  /// into a Rust value on the JavaScript thread
+  #[cfg(feature = "serde-json")]
+  pub fn eval_json<T: 'static + Send + DeserializeOwned>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self
+      .requests()
+      .request(|tx| {
+        self.eval_event(code, &Default::default(), move |env, val| {
+          tx.send(from_js_result_json(&env, Operation::Eval, val));
+        })
+      })?
+      .wait()
+  }
+
+  /// Evaluate Block of TypeScript in the context
+  pub fn eval_typescript_blocking<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.eval_with_options_blocking(
+      code,
+      &EvalOptions {
+        typescript: true,
+        ..Default::default()
+      },
+    )
+  }
+
+  /// Evaluate Block of JavaScript in the context with `EvalOptions`
+  pub fn eval_with_options_blocking<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<T> {
+    self.eval_request(code, options)?.wait()
+  }
+
+  /// Evaluate Block of JavaScript in the context, interrupting it if it runs for longer than `timeout`
+  ///
+  /// Returns `Error::Timeout` when interrupted. The context remains usable
+  /// though changes made by the script before it was interrupted are kept
+  pub fn eval_with_timeout<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    timeout: Duration,
+  ) -> crate::Result<T> {
+    self.eval_with_options_blocking(
+      code,
+      &EvalOptions {
+        timeout: Some(timeout),
+        ..Default::default()
+      },
+    )
+  }
+
+  /// Evaluate Block of JavaScript in the context without blocking the current thread
+  pub async fn eval_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self
+      .eval_with_options_async(code, &Default::default())
+      .await
+  }
+
+  /// Evaluate Block of JavaScript in the context with `EvalOptions`
+  /// without blocking the current thread
+  pub async fn eval_with_options_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<T> {
+    self.eval_request(code, options)?.await
+  }
+
+  /// Evaluate Native JavaScript
+  ///
+  /// This will provide a Nodejs Env along with the `globalThis`
+  /// of the context on the JavaScript thread.
+  /// Errors and panics of `callback` are printed to stderr,
+  /// use `exec_blocking` to receive them instead
+  pub fn exec<F: 'static + Send + FnOnce(Env, JsObject) -> crate::Result<()>>(
+    &self,
+    callback: F,
+  ) -> crate::Result<()> {
+    self
+      .requests()
+      .send(exec_event(&self.id, move |env, global| {
+        if let Err(error) = exec_result(&env, || global.and_then(|global| callback(env, global))) {
+          eprintln!("edon: exec callback failed: {}", error);
+        }
+      }))
+  }
+
+  /// Evaluate Native JavaScript and wait for it to complete
+  pub fn exec_blocking<
+    R: 'static + Send,
+    F: 'static + Send + FnOnce(Env, JsObject) -> crate::Result<R>,
+  >(
+    &self,
+    callback: F,
+  ) -> crate::Result<R> {
+    self.exec_request(callback)?.wait()
+  }
+
+  /// Evaluate Native JavaScript without blocking the current thread
+  pub async fn exec_async<
+    R: 'static + Send,
+    F: 'static + Send + FnOnce(Env, JsObject) -> crate::Result<R>,
+  >(
+    &self,
+    callback: F,
+  ) -> crate::Result<R> {
+    self.exec_request(callback)?.await
+  }
+
+  /// Load a Commonjs module into the context
+  ///
+  /// The module and its dependencies are evaluated inside the context,
+  /// builtin modules are shared with the worker
+  pub fn require(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.require_request(specifier)?.wait()
+  }
+
+  /// Load a Commonjs module into the context without blocking the current thread
+  pub async fn require_async(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.require_request(specifier)?.await
+  }
+
+  /// Import an ES module from the context
+  ///
+  /// The module is loaded by the worker's ESM loader, the same as `import()`
+  /// called by code evaluated in the context. Node has no ESM loader per vm context
+  /// so the module is evaluated in the realm of the worker and shares its module cache
+  pub fn import(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.import_request(specifier)?.wait()
+  }
+
+  /// Import an ES module from the context without blocking the current thread
+  pub async fn import_async(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.import_request(specifier)?.await
+  }
+
+  fn requests(&self) -> Requests<'_, NodejsWorkerEvent> {
+    Requests::new(&self.tx_wrk, Some(&self.state))
+  }
+
+  fn eval_request<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<Pending<T>> {
+    self.requests().request(|tx| {
+      self.eval_event(code, options, move |env, val| {
+        tx.send(from_js_result(&env, Operation::Eval, val));
+      })
+    })
+  }
+
+  fn exec_request<
+    R: 'static + Send,
+    F: 'static + Send + FnOnce(Env, JsObject) -> crate::Result<R>,
+  >(
+    &self,
+    callback: F,
+  ) -> crate::Result<Pending<R>> {
+    self.requests().request(|tx| {
+      exec_event(&self.id, move |env, global| {
+        tx.send(exec_result(&env, || {
+          global.and_then(|global| callback(env, global))
+        }));
+      })
+    })
+  }
+
+  fn require_request(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<Pending<()>> {
+    self
+      .requests()
+      .request(|tx| NodejsWorkerEvent::ContextRequire {
+        id: self.id.clone(),
+        specifier: specifier.as_ref().to_string(),
+        resolve: Box::new(move |result| tx.send(result)),
+      })
+  }
+
+  fn import_request(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<Pending<()>> {
+    self
+      .requests()
+      .request(|tx| NodejsWorkerEvent::ContextImport {
+        id: self.id.clone(),
+        specifier: specifier.as_ref().to_string(),
+        resolve: Box::new(move |result| tx.send(result)),
+      })
+  }
+
+  fn eval_event(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+    callback: impl 'static + Send + FnOnce(Env, crate::Result<JsUnknown>),
+  ) -> NodejsWorkerEvent {
+    NodejsWorkerEvent::ContextEval {
+      id: self.id.clone(),
+      code: code.as_ref().to_string(),
+      typescript: options.typescript,
+      await_promise: options.await_promise,
+      timeout: options.timeout,
+      callback: Box::new(callback),
+    }
+  }
+}
+
+impl Drop for NodejsContext {
+  fn drop(&mut self) {
+    self
+      .tx_wrk
+      .send(NodejsWorkerEvent::DestroyContext {
+        id: self.id.clone(),
+      })
+      .ok();
+  }
+}
+
+fn exec_event(
+  id: &str,
+  callback: impl 'static + Send + FnOnce(Env, crate::Result<JsObject>),
+) -> NodejsWorkerEvent {
+  NodejsWorkerEvent::ContextExec {
+    id: id.to_string(),
+    callback: Box::new(move |env, global| {
+      callback(
+        env,
+        global.and_then(|global| Ok(JsObject::from_unknown(global)?)),
+      )
+    }),
+  }
+}
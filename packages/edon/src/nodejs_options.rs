@@ -86,7 +86,7 @@ impl NodejsOptions {
     }
 
     if let Some(true) = &self.inspect_brk {
-      argv.push(format!("--inspect-brk"));
+      argv.push("--inspect-brk".to_string());
     }
 
     if let Some(inspect_port) = &self.inspect_port {
@@ -94,7 +94,7 @@ impl NodejsOptions {
     }
 
     if let Some(true) = &self.expose_gc {
-      argv.push(format!("--expose-gc"));
+      argv.push("--expose-gc".to_string());
     }
 
     if let Some(snapshot_blob) = &self.snapshot_blob {
@@ -1,161 +1,263 @@
-use std::sync::atomic::Ordering;
 use std::sync::mpsc::channel;
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
 
+#[cfg(feature = "serde-json")]
+use serde::de::DeserializeOwned;
+
+use crate::internal::ContextSender;
 use crate::internal::NodejsMainEvent;
 use crate::internal::NodejsWorkerEvent;
+use crate::internal::RequestEvent;
+use crate::internal::Requests;
+use crate::internal::WorkerState;
+use crate::napi::bindgen_prelude::FromNapiValue;
 use crate::napi::JsUnknown;
+use crate::Channel;
+use crate::ChannelValue;
 use crate::Env;
-use crate::NodejsOptions;
-use crate::NODEJS_CONTEXT_COUNT;
+use crate::EvalOptions;
+use crate::HeapStatistics;
+use crate::HeapStatisticsSampler;
+use crate::NodejsContext;
+use crate::NodejsContextOptions;
+use crate::WorkerOptions;
+
+/// Whether a Nodejs worker thread is still alive
+#[derive(Debug, Clone)]
+pub enum WorkerStatus {
+  Running,
+  Exited(WorkerExit),
+}
+
+/// How a Nodejs worker thread exited
+#[derive(Debug, Clone)]
+pub struct WorkerExit {
+  /// Exit code of the worker, as passed to `process.exit()`.
+  /// Workers killed by an uncaught exception or stopped by the host exit with 1
+  pub code: i32,
+  /// The uncaught exception that killed the worker
+  pub error: Option<Box<crate::Error>>,
+}
+
+/// How long workers that are dropped, or stopped by `Nodejs::shutdown`,
+/// are given to shut down cleanly before their execution is terminated
+pub const DEFAULT_WORKER_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a Nodejs worker thread was stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerStop {
+  /// The worker finished its pending work and shut down
+  Clean,
+  /// The worker did not shut down in time and its execution was terminated
+  Forced,
+}
 
 pub struct NodejsWorker {
   id: String,
   tx_main: Sender<NodejsMainEvent>,
   tx_wrk: Sender<NodejsWorkerEvent>,
+  state: Arc<WorkerState>,
+  stopped: bool,
 }
 
 impl NodejsWorker {
   pub(crate) fn start(
-    options: &NodejsOptions,
+    options: &WorkerOptions,
     tx_main: Sender<NodejsMainEvent>,
   ) -> crate::Result<Self> {
-    NODEJS_CONTEXT_COUNT.fetch_add(1, Ordering::AcqRel);
     let (tx, rx) = channel();
     let (tx_wrk, rx_wrk) = channel::<NodejsWorkerEvent>();
+    let state = Arc::new(WorkerState::default());
 
     tx_main
       .send(NodejsMainEvent::StartWorker {
         rx_wrk,
-        argv: options.as_argv(),
+        options: options.clone(),
+        state: state.clone(),
         resolve: tx,
       })
-      .ok();
+      .map_err(|_| crate::Error::NodejsNotRunning)?;
 
-    let id = rx.recv().unwrap();
+    let id = rx.recv().map_err(|_| crate::Error::NodejsNotRunning)??;
 
-    return Ok(Self {
+    // The worker failed before it was ready, e.g. it called process.exit()
+    if state.is_exited() {
+      return Err(state.closed_error());
+    }
+
+    Ok(Self {
       id,
       tx_main,
       tx_wrk,
-    });
+      state,
+      stopped: false,
+    })
   }
 
-    pub fn eval<Code: AsRef<str>>(
+  pub fn eval<Code: AsRef<str>>(
     &self,
     code: Code,
-    callback: impl 'static + Send + FnOnce(Env, JsUnknown),
+    callback: impl 'static + Send + FnOnce(Env, crate::Result<JsUnknown>),
   ) -> crate::Result<()> {
-    self
-      .tx_wrk
-      .send(NodejsWorkerEvent::Eval {
-        code: code.as_ref().to_string(),
-        callback: Box::new(callback),
-      })
-      .unwrap();
-
-    Ok(())
+    self.requests().send(NodejsWorkerEvent::eval(
+      code.as_ref().to_string(),
+      &Default::default(),
+      Box::new(callback),
+    ))
   }
 
   /// Evaluate Block of Commonjs JavaScript
   ///
-  /// The last line of the script will be returned
-  pub fn eval_blocking<Code: AsRef<str>>(
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread. Evaluate as `()` to ignore the value
+  pub fn eval_blocking<T: 'static + Send + FromNapiValue>(
     &self,
-    code: Code,
-  ) -> crate::Result<()> {
-    let (tx, rx) = channel();
-
-    self
-      .tx_wrk
-      .send(NodejsWorkerEvent::Eval {
-        code: code.as_ref().to_string(),
-        callback: Box::new(move |_env, _val| {
-          tx.send(Ok(())).unwrap();
-        }),
-      })
-      .ok();
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, &Default::default())?.wait()
+  }
 
-    rx.recv().unwrap()
+  /// Evaluate Block of Commonjs JavaScript
+  ///
+  /// The last line of the script will be returned, deserialized
+  /// into a Rust value on the JavaScript thread
+  #[cfg(feature = "serde-json")]
+  pub fn eval_json<T: 'static + Send + DeserializeOwned>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.requests().eval_json(code, &Default::default())?.wait()
   }
 
   /// Evaluate Block of ESM JavaScript
   pub fn eval_typescript<Code: AsRef<str>>(
     &self,
     code: Code,
-    callback: impl 'static + Send + FnOnce(Env, JsUnknown),
+    callback: impl 'static + Send + FnOnce(Env, crate::Result<JsUnknown>),
   ) -> crate::Result<()> {
-    self
-      .tx_wrk
-      .send(NodejsWorkerEvent::EvalTypeScript {
-        code: code.as_ref().to_string(),
-        callback: Box::new(callback),
-      })
-      .unwrap();
-
-    Ok(())
+    self.requests().send(NodejsWorkerEvent::eval(
+      code.as_ref().to_string(),
+      &EvalOptions {
+        typescript: true,
+        ..Default::default()
+      },
+      Box::new(callback),
+    ))
   }
 
   /// Evaluate Block of ESM JavaScript
-  pub fn eval_typescript_blocking<Code: AsRef<str>>(
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub fn eval_typescript_blocking<T: 'static + Send + FromNapiValue>(
     &self,
-    code: Code,
-  ) -> crate::Result<()> {
-    let (tx, rx) = channel();
-
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
     self
-      .tx_wrk
-      .send(NodejsWorkerEvent::EvalTypeScript {
-        code: code.as_ref().to_string(),
-        callback: Box::new(move |_env, _val| {
-          tx.send(Ok(())).unwrap();
-        }),
-      })
-      .ok();
+      .requests()
+      .eval(
+        code,
+        &EvalOptions {
+          typescript: true,
+          ..Default::default()
+        },
+      )?
+      .wait()
+  }
 
-    rx.recv().unwrap()
+  /// Evaluate Block of JavaScript with options
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub fn eval_with_options_blocking<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, options)?.wait()
+  }
+
+  /// Evaluate Block of JavaScript with options without blocking the current thread
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub async fn eval_with_options_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    options: &EvalOptions,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, options)?.await
+  }
+
+  /// Evaluate Block of Commonjs JavaScript, interrupting it if it runs for longer than `timeout`
+  ///
+  /// Returns `Error::Timeout` when interrupted. The context remains usable
+  /// though changes made by the script before it was interrupted are kept
+  pub fn eval_with_timeout<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+    timeout: Duration,
+  ) -> crate::Result<T> {
+    self.eval_with_options_blocking(
+      code,
+      &EvalOptions {
+        timeout: Some(timeout),
+        ..Default::default()
+      },
+    )
   }
 
   /// Evaluate Native JavaScript
   ///
   /// This will provide a Nodejs Env and allow execution of
   /// native code in the JavaScript context
-  pub fn exec_blocking<F: 'static + Send + FnOnce(Env) -> crate::Result<()>>(
+  pub fn exec_blocking<R: 'static + Send, F: 'static + Send + FnOnce(Env) -> crate::Result<R>>(
     &self,
     callback: F,
-  ) -> crate::Result<()> {
-    let (tx, rx) = channel();
-
-    self
-      .tx_wrk
-      .send(NodejsWorkerEvent::Exec {
-        callback: Box::new(move |env| {
-          let result = callback(env);
-          tx.send(Ok(())).unwrap();
-          result
-        }),
-      })
-      .ok();
-
-    rx.recv().unwrap()
+  ) -> crate::Result<R> {
+    self.requests().exec(callback)?.wait()
   }
 
   /// Evaluate Native JavaScript
   ///
   /// This will provide a Nodejs Env and allow execution of
-  /// native code in the JavaScript context
+  /// native code in the JavaScript context.
+  /// Errors and panics of `callback` are printed to stderr,
+  /// use `exec_blocking` to receive them instead
   pub fn exec<F: 'static + Send + FnOnce(Env) -> crate::Result<()>>(
     &self,
     callback: F,
   ) -> crate::Result<()> {
     self
-      .tx_wrk
-      .send(NodejsWorkerEvent::Exec {
-        callback: Box::new(callback),
-      })
-      .unwrap();
-    
-    Ok(())
+      .requests()
+      .send(NodejsWorkerEvent::exec(Box::new(callback)))
+  }
+
+  /// Collect the heap statistics of the worker thread
+  pub fn heap_statistics(&self) -> crate::Result<HeapStatistics> {
+    self.exec_blocking(|env| HeapStatistics::collect(&env))
+  }
+
+  /// Call `callback` with the heap statistics of the worker thread
+  /// every `interval` until the returned sampler is dropped or the worker exits
+  pub fn sample_heap_statistics(
+    &self,
+    interval: Duration,
+    callback: impl 'static + Send + FnMut(crate::Result<HeapStatistics>),
+  ) -> HeapStatisticsSampler {
+    let context = ContextSender::Worker(self.tx_wrk.clone(), self.state.clone());
+    let is_closed = {
+      let state = self.state.clone();
+      move || state.is_exited()
+    };
+    HeapStatisticsSampler::start(
+      interval,
+      move || context.exec_blocking(|env| HeapStatistics::collect(&env)),
+      is_closed,
+      callback,
+    )
   }
 
   /// Call Nodejs's require() function to import code
@@ -163,17 +265,7 @@ impl NodejsWorker {
     &self,
     specifier: Specifier,
   ) -> crate::Result<()> {
-    let (tx, rx) = channel();
-
-    self
-      .tx_wrk
-      .send(NodejsWorkerEvent::Require {
-        specifier: specifier.as_ref().to_string(),
-        resolve: tx,
-      })
-      .ok();
-
-    rx.recv().unwrap()
+    self.requests().require(specifier)?.wait()
   }
 
   /// Call Nodejs's await import() to import code
@@ -181,40 +273,162 @@ impl NodejsWorker {
     &self,
     specifier: Specifier,
   ) -> crate::Result<()> {
-    let (tx, rx) = channel();
+    self.requests().import(specifier)?.wait()
+  }
+
+  /// Create an isolated vm context within the worker
+  pub fn create_context(&self) -> crate::Result<NodejsContext> {
+    self.create_context_with_options(&Default::default())
+  }
 
+  pub fn create_context_with_options(
+    &self,
+    options: &NodejsContextOptions,
+  ) -> crate::Result<NodejsContext> {
+    NodejsContext::start(options, self.tx_wrk.clone(), self.state.clone())
+  }
+
+  /// Open a two way message channel with JavaScript
+  ///
+  /// The JavaScript end is assigned to `globalThis[name]`
+  pub fn open_channel<In: ChannelValue, Out: ChannelValue>(
+    &self,
+    name: impl AsRef<str>,
+  ) -> crate::Result<Channel<In, Out>> {
+    Channel::open(
+      ContextSender::Worker(self.tx_wrk.clone(), self.state.clone()),
+      name,
+    )
+  }
+
+  /// Evaluate Block of Commonjs JavaScript without blocking the current thread
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub async fn eval_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.requests().eval(code, &Default::default())?.await
+  }
+
+  /// Evaluate Block of ESM JavaScript without blocking the current thread
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread
+  pub async fn eval_typescript_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
     self
-      .tx_wrk
-      .send(NodejsWorkerEvent::Import {
-        specifier: specifier.as_ref().to_string(),
-        resolve: tx,
-      })
-      .ok();
+      .requests()
+      .eval(
+        code,
+        &EvalOptions {
+          typescript: true,
+          ..Default::default()
+        },
+      )?
+      .await
+  }
 
-    rx.recv().unwrap()
+  /// Evaluate Native JavaScript without blocking the current thread
+  ///
+  /// This will provide a Nodejs Env and allow execution of
+  /// native code in the JavaScript context
+  pub async fn exec_async<
+    R: 'static + Send,
+    F: 'static + Send + FnOnce(Env) -> crate::Result<R>,
+  >(
+    &self,
+    callback: F,
+  ) -> crate::Result<R> {
+    self.requests().exec(callback)?.await
   }
-}
 
-impl Drop for NodejsWorker {
-  fn drop(&mut self) {
+  /// Call Nodejs's require() function to import code without
+  /// blocking the current thread
+  pub async fn require_async(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.requests().require(specifier)?.await
+  }
+
+  /// Call Nodejs's await import() to import code without
+  /// blocking the current thread
+  pub async fn import_async(
+    &self,
+    specifier: impl AsRef<str>,
+  ) -> crate::Result<()> {
+    self.requests().import(specifier)?.await
+  }
+
+  /// Whether the worker is still running or how it exited
+  pub fn status(&self) -> WorkerStatus {
+    self.state.status()
+  }
+
+  /// Call `callback` when the worker exits, including when it dies from
+  /// an uncaught exception or calls `process.exit()`
+  ///
+  /// The callback runs on the thread of the main Nodejs context,
+  /// straight away if the worker has already exited. Requests sent
+  /// to a worker that has exited return `Error::WorkerExited`
+  pub fn on_exit(
+    &self,
+    callback: impl 'static + Send + FnOnce(WorkerExit),
+  ) {
+    self.state.on_exit(Box::new(callback));
+  }
+
+  /// Stop the worker, waiting up to `timeout` for it to shut down cleanly
+  ///
+  /// A worker that doesn't shut down in time (e.g. it is stuck in an infinite loop)
+  /// has its execution terminated, which is reported as `WorkerStop::Forced`.
+  /// A worker that has already exited returns `Error::WorkerExited` or
+  /// `Error::WorkerOutOfMemory`, `status()` holds how it exited
+  pub fn terminate(
+    mut self,
+    timeout: Duration,
+  ) -> crate::Result<WorkerStop> {
+    self.stop(timeout)?.ok_or_else(|| self.state.closed_error())
+  }
+
+  /// Stop the worker, `None` if it had already exited
+  fn stop(
+    &mut self,
+    timeout: Duration,
+  ) -> crate::Result<Option<WorkerStop>> {
+    self.stopped = true;
+
     let (tx, rx) = channel();
     self
       .tx_main
       .send(NodejsMainEvent::StopWorker {
         id: self.id.clone(),
+        timeout,
         resolve: tx,
       })
-      .unwrap();
-    rx.recv().unwrap();
-
-    let context_count = NODEJS_CONTEXT_COUNT.fetch_sub(1, Ordering::AcqRel);
-    if context_count == 1 {
-      let (tx, rx) = channel();
-      self
-        .tx_main
-        .send(NodejsMainEvent::StopMain { resolve: tx })
-        .unwrap();
-      rx.recv().unwrap();
+      .map_err(|_| crate::Error::NodejsNotRunning)?;
+    rx.recv().map_err(|_| crate::Error::NodejsNotRunning)
+  }
+
+  /// True once the worker has exited, or its requests can no longer be delivered
+  pub(crate) fn is_exited(&self) -> bool {
+    self.state.is_exited()
+  }
+
+  pub(crate) fn requests(&self) -> Requests<'_, NodejsWorkerEvent> {
+    Requests::new(&self.tx_wrk, Some(&self.state))
+  }
+}
+
+impl Drop for NodejsWorker {
+  fn drop(&mut self) {
+    // Workers are already stopped if Nodejs was shut down
+    if !self.stopped {
+      self.stop(DEFAULT_WORKER_STOP_TIMEOUT).ok();
     }
   }
 }
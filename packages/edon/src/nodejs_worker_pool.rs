@@ -0,0 +1,493 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+
+use crate::internal::from_js_result;
+use crate::internal::NodejsWorkerEvent;
+use crate::internal::Pending;
+use crate::internal::RequestEvent;
+use crate::napi::bindgen_prelude::FromNapiValue;
+use crate::Env;
+use crate::NodejsWorker;
+use crate::Operation;
+use crate::WorkerOptions;
+use crate::WorkerStop;
+use crate::DEFAULT_WORKER_STOP_TIMEOUT;
+
+/// How jobs are assigned to the workers of a `NodejsWorkerPool`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerPoolDispatch {
+  /// Hand jobs to each worker in turn
+  #[default]
+  RoundRobin,
+  /// Hand jobs to the worker with the fewest jobs in flight
+  LeastBusy,
+}
+
+/// Options for a pool of Nodejs worker threads
+#[derive(Debug, Clone)]
+pub struct NodejsWorkerPoolOptions {
+  /// Number of workers to spawn
+  pub size: usize,
+  /// How jobs are assigned to workers
+  pub dispatch: WorkerPoolDispatch,
+  /// Maximum number of jobs in flight across the whole pool.
+  ///
+  /// Once reached, submitting a job waits for a running job to complete
+  /// and the `try_*` methods return `Error::WorkerPoolQueueFull`
+  pub queue_capacity: usize,
+  /// Options used to spawn each worker
+  pub worker_options: WorkerOptions,
+}
+
+impl Default for NodejsWorkerPoolOptions {
+  fn default() -> Self {
+    let size = std::thread::available_parallelism()
+      .map(|size| size.get())
+      .unwrap_or(1);
+
+    Self {
+      size,
+      dispatch: Default::default(),
+      queue_capacity: size * 16,
+      worker_options: Default::default(),
+    }
+  }
+}
+
+/// A fixed size pool of Nodejs worker threads
+///
+/// Jobs are dispatched to the workers according to `WorkerPoolDispatch` and
+/// the number of jobs in flight is bounded by `queue_capacity`.
+/// Workers that exit are not replaced, jobs are dispatched to the remaining ones
+pub struct NodejsWorkerPool {
+  workers: Vec<PooledWorker>,
+  dispatch: WorkerPoolDispatch,
+  next: AtomicUsize,
+  queue: Arc<JobQueue>,
+}
+
+struct PooledWorker {
+  worker: NodejsWorker,
+  in_flight: Arc<AtomicUsize>,
+}
+
+impl NodejsWorkerPool {
+  pub(crate) fn start(
+    options: &NodejsWorkerPoolOptions,
+    spawn_worker: impl Fn(&WorkerOptions) -> crate::Result<NodejsWorker>,
+  ) -> crate::Result<Self> {
+    if options.size == 0 {
+      return Err(crate::Error::generic("Worker pool size must be at least 1"));
+    }
+
+    if options.queue_capacity == 0 {
+      return Err(crate::Error::generic(
+        "Worker pool queue capacity must be at least 1",
+      ));
+    }
+
+    let mut workers = vec![];
+    for _ in 0..options.size {
+      workers.push(PooledWorker {
+        worker: spawn_worker(&options.worker_options)?,
+        in_flight: Default::default(),
+      });
+    }
+
+    Ok(Self {
+      workers,
+      dispatch: options.dispatch,
+      next: AtomicUsize::new(0),
+      queue: Arc::new(JobQueue {
+        capacity: options.queue_capacity,
+        state: Default::default(),
+        condvar: Condvar::new(),
+      }),
+    })
+  }
+
+  /// Number of workers in the pool
+  pub fn size(&self) -> usize {
+    self.workers.len()
+  }
+
+  /// Number of jobs currently in flight across the pool
+  pub fn pending(&self) -> usize {
+    self.queue.state.lock().unwrap().pending
+  }
+
+  /// Iterate over the workers in the pool, e.g. to prepare each context
+  pub fn workers(&self) -> impl Iterator<Item = &NodejsWorker> {
+    self.workers.iter().map(|pooled| &pooled.worker)
+  }
+
+  /// Submit a block of Commonjs JavaScript to the pool
+  ///
+  /// This blocks the current thread while the queue is full
+  pub fn eval_job<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<WorkerPoolJob<T>> {
+    self.queue.acquire();
+    self.submit_eval(code)
+  }
+
+  /// Submit a block of Commonjs JavaScript to the pool
+  ///
+  /// Returns `Error::WorkerPoolQueueFull` rather than waiting when the queue is full
+  pub fn try_eval_job<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<WorkerPoolJob<T>> {
+    if !self.queue.try_acquire() {
+      return Err(crate::Error::WorkerPoolQueueFull);
+    }
+    self.submit_eval(code)
+  }
+
+  /// Evaluate a block of Commonjs JavaScript on one of the workers
+  ///
+  /// The last line of the script will be returned, converted
+  /// into a Rust value on the JavaScript thread. Evaluate as `()` to ignore the value
+  pub fn eval_blocking<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    self.eval_job(code)?.wait()
+  }
+
+  /// Evaluate a block of Commonjs JavaScript on one of the workers
+  /// without blocking the current thread
+  pub async fn eval_async<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<T> {
+    Acquire(&self.queue).await;
+    self.submit_eval(code)?.await
+  }
+
+  /// Submit native code to run on one of the workers
+  ///
+  /// This blocks the current thread while the queue is full
+  pub fn exec_job<R: 'static + Send, F: 'static + Send + FnOnce(Env) -> crate::Result<R>>(
+    &self,
+    callback: F,
+  ) -> crate::Result<WorkerPoolJob<R>> {
+    self.queue.acquire();
+    self.submit_exec(callback)
+  }
+
+  /// Submit native code to run on one of the workers
+  ///
+  /// Returns `Error::WorkerPoolQueueFull` rather than waiting when the queue is full
+  pub fn try_exec_job<R: 'static + Send, F: 'static + Send + FnOnce(Env) -> crate::Result<R>>(
+    &self,
+    callback: F,
+  ) -> crate::Result<WorkerPoolJob<R>> {
+    if !self.queue.try_acquire() {
+      return Err(crate::Error::WorkerPoolQueueFull);
+    }
+    self.submit_exec(callback)
+  }
+
+  /// Run native code on one of the workers
+  pub fn exec_blocking<R: 'static + Send, F: 'static + Send + FnOnce(Env) -> crate::Result<R>>(
+    &self,
+    callback: F,
+  ) -> crate::Result<R> {
+    self.exec_job(callback)?.wait()
+  }
+
+  /// Run native code on one of the workers without blocking the current thread
+  pub async fn exec_async<
+    R: 'static + Send,
+    F: 'static + Send + FnOnce(Env) -> crate::Result<R>,
+  >(
+    &self,
+    callback: F,
+  ) -> crate::Result<R> {
+    Acquire(&self.queue).await;
+    self.submit_exec(callback)?.await
+  }
+
+  /// Stop every worker in the pool, waiting up to `timeout` for each to shut down cleanly
+  ///
+  /// Workers are stopped concurrently. Those that don't shut down in time
+  /// have their execution terminated, see `NodejsWorker::terminate`.
+  /// Workers that had already exited are left out of the result
+  pub fn shutdown_with_timeout(
+    mut self,
+    timeout: Duration,
+  ) -> crate::Result<Vec<WorkerStop>> {
+    stop_workers(std::mem::take(&mut self.workers), timeout)
+  }
+
+  fn submit_eval<T: 'static + Send + FromNapiValue>(
+    &self,
+    code: impl AsRef<str>,
+  ) -> crate::Result<WorkerPoolJob<T>> {
+    let (worker, guard) = self.next_worker();
+
+    let pending = worker.requests().request(|tx| {
+      NodejsWorkerEvent::eval(
+        code.as_ref().to_string(),
+        &Default::default(),
+        Box::new(move |env, val| {
+          let _guard = guard;
+          tx.send(from_js_result(&env, Operation::Eval, val));
+        }),
+      )
+    })?;
+
+    Ok(WorkerPoolJob(pending))
+  }
+
+  fn submit_exec<R: 'static + Send, F: 'static + Send + FnOnce(Env) -> crate::Result<R>>(
+    &self,
+    callback: F,
+  ) -> crate::Result<WorkerPoolJob<R>> {
+    let (worker, guard) = self.next_worker();
+
+    let pending = worker.requests().exec(move |env| {
+      let _guard = guard;
+      callback(env)
+    })?;
+
+    Ok(WorkerPoolJob(pending))
+  }
+
+  /// Select a worker for a job that has already been admitted by the queue
+  ///
+  /// Workers that have exited are skipped. Once every worker has exited
+  /// one is returned anyway so the job fails with the reason it exited
+  fn next_worker(&self) -> (&NodejsWorker, JobGuard) {
+    let alive = |index: &usize| !self.workers[*index].worker.is_exited();
+
+    let index = match self.dispatch {
+      WorkerPoolDispatch::RoundRobin => {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (start..start + self.size())
+          .map(|index| index % self.size())
+          .find(alive)
+          .unwrap_or(start % self.size())
+      }
+      WorkerPoolDispatch::LeastBusy => (0..self.size())
+        .filter(alive)
+        .min_by_key(|index| self.workers[*index].in_flight.load(Ordering::Acquire))
+        .unwrap_or_default(),
+    };
+
+    let pooled = &self.workers[index];
+    pooled.in_flight.fetch_add(1, Ordering::AcqRel);
+
+    (
+      &pooled.worker,
+      JobGuard {
+        queue: self.queue.clone(),
+        in_flight: pooled.in_flight.clone(),
+      },
+    )
+  }
+}
+
+impl Drop for NodejsWorkerPool {
+  fn drop(&mut self) {
+    // Stopped together rather than by each worker's own drop, one after another
+    stop_workers(
+      std::mem::take(&mut self.workers),
+      DEFAULT_WORKER_STOP_TIMEOUT,
+    )
+    .ok();
+  }
+}
+
+/// Stop `workers` concurrently, leaving out those that had already exited
+fn stop_workers(
+  workers: Vec<PooledWorker>,
+  timeout: Duration,
+) -> crate::Result<Vec<WorkerStop>> {
+  std::thread::scope(|scope| {
+    let handles = workers
+      .into_iter()
+      .map(|pooled| scope.spawn(move || pooled.worker.terminate(timeout)))
+      .collect::<Vec<_>>();
+
+    handles
+      .into_iter()
+      .filter_map(|handle| match handle.join().unwrap() {
+        Err(crate::Error::WorkerExited | crate::Error::WorkerOutOfMemory) => None,
+        result => Some(result),
+      })
+      .collect()
+  })
+}
+
+/// Result of a job submitted to a `NodejsWorkerPool`
+///
+/// Use `wait` to block on the result or `.await` it. A job whose
+/// worker exits before completing fails with `Error::WorkerExited`
+/// (or `Error::WorkerOutOfMemory`)
+pub struct WorkerPoolJob<T>(Pending<T>);
+
+impl<T> WorkerPoolJob<T> {
+  /// Block the current thread until the job completes
+  pub fn wait(self) -> crate::Result<T> {
+    self.0.wait()
+  }
+}
+
+impl<T> Future for WorkerPoolJob<T> {
+  type Output = crate::Result<T>;
+
+  fn poll(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    Pin::new(&mut self.0).poll(cx)
+  }
+}
+
+/// Counts the jobs in flight and makes submitters wait once it is full
+struct JobQueue {
+  capacity: usize,
+  state: Mutex<JobQueueState>,
+  condvar: Condvar,
+}
+
+#[derive(Default)]
+struct JobQueueState {
+  pending: usize,
+  wakers: Vec<Waker>,
+}
+
+impl JobQueue {
+  fn acquire(&self) {
+    let mut state = self
+      .condvar
+      .wait_while(self.state.lock().unwrap(), |state| {
+        state.pending >= self.capacity
+      })
+      .unwrap();
+    state.pending += 1;
+  }
+
+  fn try_acquire(&self) -> bool {
+    let mut state = self.state.lock().unwrap();
+    if state.pending >= self.capacity {
+      return false;
+    }
+    state.pending += 1;
+    true
+  }
+
+  fn poll_acquire(
+    &self,
+    cx: &mut Context<'_>,
+  ) -> Poll<()> {
+    let mut state = self.state.lock().unwrap();
+    if state.pending >= self.capacity {
+      state.wakers.push(cx.waker().clone());
+      return Poll::Pending;
+    }
+    state.pending += 1;
+    Poll::Ready(())
+  }
+
+  fn release(&self) {
+    let mut state = self.state.lock().unwrap();
+    state.pending -= 1;
+    for waker in state.wakers.drain(..) {
+      waker.wake();
+    }
+    self.condvar.notify_one();
+  }
+}
+
+struct Acquire<'a>(&'a JobQueue);
+
+impl Future for Acquire<'_> {
+  type Output = ();
+
+  fn poll(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    self.0.poll_acquire(cx)
+  }
+}
+
+/// Releases a job's slot in the queue and on its worker once the job completes
+struct JobGuard {
+  queue: Arc<JobQueue>,
+  in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for JobGuard {
+  fn drop(&mut self) {
+    self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    self.queue.release();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::thread;
+
+  use super::*;
+
+  fn job_queue(capacity: usize) -> Arc<JobQueue> {
+    Arc::new(JobQueue {
+      capacity,
+      state: Default::default(),
+      condvar: Condvar::new(),
+    })
+  }
+
+  #[test]
+  fn try_acquire_fails_once_the_queue_is_full() {
+    let queue = job_queue(2);
+    assert!(queue.try_acquire());
+    assert!(queue.try_acquire());
+    assert!(!queue.try_acquire());
+
+    queue.release();
+    assert!(queue.try_acquire());
+  }
+
+  #[test]
+  fn acquire_waits_for_a_release() {
+    let queue = job_queue(1);
+    queue.acquire();
+
+    let handle = thread::spawn({
+      let queue = queue.clone();
+      move || queue.acquire()
+    });
+    thread::sleep(Duration::from_millis(10));
+    assert!(!handle.is_finished());
+
+    queue.release();
+    handle.join().unwrap();
+    assert_eq!(queue.state.lock().unwrap().pending, 1);
+  }
+
+  #[test]
+  fn poll_acquire_is_pending_until_a_release() {
+    let queue = job_queue(1);
+    let mut cx = Context::from_waker(Waker::noop());
+    assert!(queue.poll_acquire(&mut cx).is_ready());
+    assert!(queue.poll_acquire(&mut cx).is_pending());
+
+    queue.release();
+    assert!(queue.poll_acquire(&mut cx).is_ready());
+  }
+}
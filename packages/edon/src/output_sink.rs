@@ -0,0 +1,92 @@
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+pub type OutputCallback = Arc<dyn Fn(&[u8]) + Send + Sync>;
+
+/// Destination for the stdout or stderr of a Nodejs worker
+///
+/// Output is delivered in chunks as it is written by the worker,
+/// on the thread of the main Nodejs context
+#[derive(Clone, Default)]
+pub enum OutputSink {
+  /// Forward output to the stdout/stderr of the host process
+  #[default]
+  Inherit,
+  /// Discard output
+  Ignore,
+  /// Call a function with each chunk of output
+  Callback(OutputCallback),
+  /// Write output into a `std::io::Write`
+  Writer(Arc<Mutex<dyn Write + Send>>),
+  /// Collect output into an in-memory buffer
+  Buffer(OutputBuffer),
+}
+
+impl OutputSink {
+  pub fn callback(callback: impl 'static + Fn(&[u8]) + Send + Sync) -> Self {
+    Self::Callback(Arc::new(callback))
+  }
+
+  pub fn writer(writer: impl 'static + Write + Send) -> Self {
+    Self::Writer(Arc::new(Mutex::new(writer)))
+  }
+
+  pub(crate) fn is_inherit(&self) -> bool {
+    matches!(self, Self::Inherit)
+  }
+
+  pub(crate) fn write(
+    &self,
+    data: &[u8],
+  ) {
+    match self {
+      Self::Inherit | Self::Ignore => {}
+      Self::Callback(callback) => callback(data),
+      Self::Writer(writer) => {
+        let mut writer = writer.lock().unwrap();
+        writer.write_all(data).ok();
+        writer.flush().ok();
+      }
+      Self::Buffer(buffer) => buffer.0.lock().unwrap().extend_from_slice(data),
+    }
+  }
+}
+
+impl std::fmt::Debug for OutputSink {
+  fn fmt(
+    &self,
+    f: &mut std::fmt::Formatter<'_>,
+  ) -> std::fmt::Result {
+    match self {
+      Self::Inherit => write!(f, "Inherit"),
+      Self::Ignore => write!(f, "Ignore"),
+      Self::Callback(_) => write!(f, "Callback"),
+      Self::Writer(_) => write!(f, "Writer"),
+      Self::Buffer(buffer) => f.debug_tuple("Buffer").field(buffer).finish(),
+    }
+  }
+}
+
+/// Shared in-memory buffer that worker output is collected into
+///
+/// Clones refer to the same buffer
+#[derive(Debug, Default, Clone)]
+pub struct OutputBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl OutputBuffer {
+  /// Copy of the output collected so far
+  pub fn contents(&self) -> Vec<u8> {
+    self.0.lock().unwrap().clone()
+  }
+
+  /// Output collected so far, decoded as UTF-8
+  pub fn to_string_lossy(&self) -> String {
+    String::from_utf8_lossy(&self.0.lock().unwrap()).to_string()
+  }
+
+  /// Take the output collected so far, leaving the buffer empty
+  pub fn take(&self) -> Vec<u8> {
+    std::mem::take(&mut *self.0.lock().unwrap())
+  }
+}
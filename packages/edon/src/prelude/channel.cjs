@@ -0,0 +1,32 @@
+// Creates the JavaScript end of a Channel
+//
+// postMessage and close are native functions that forward to the Rust end
+(function createChannel(postMessage, close) {
+  const { EventEmitter } = process.getBuiltinModule("node:events");
+
+  let closed = false;
+
+  const channel = new EventEmitter();
+
+  // Send a value to Rust
+  channel.postMessage = (value) => {
+    if (closed) {
+      throw new Error("Channel is closed");
+    }
+    postMessage(value);
+  };
+
+  // Close both ends of the channel
+  channel.close = () => {
+    if (closed) return;
+    closed = true;
+    close();
+    channel.emit("close");
+  };
+
+  Object.defineProperty(channel, "closed", {
+    get: () => closed,
+  });
+
+  return channel;
+})
@@ -0,0 +1,28 @@
+// Collects the memory usage of the current thread, see HeapStatistics
+//
+// Event loop utilization is measured since the previous call on this
+// thread, or since the thread started for the first call
+(function heapStatistics() {
+  const v8 = process.getBuiltinModule("node:v8");
+  const { performance } = process.getBuiltinModule("node:perf_hooks");
+  const { isMainThread, resourceLimits } = process.getBuiltinModule("node:worker_threads");
+
+  const previous = Symbol.for("edon:eventLoopUtilization");
+  const current = performance.eventLoopUtilization();
+  const eventLoopUtilization = globalThis[previous]
+    ? performance.eventLoopUtilization(current, globalThis[previous])
+    : current;
+  Object.defineProperty(globalThis, previous, {
+    value: current,
+    configurable: true,
+    enumerable: false,
+    writable: true,
+  });
+
+  return {
+    heap: v8.getHeapStatistics(),
+    memory: process.memoryUsage(),
+    resourceLimits: isMainThread ? null : resourceLimits,
+    eventLoopUtilization,
+  };
+});
@@ -1,18 +1,124 @@
 void (function () {
   // This is a shim that adds in the functionality 
   // which will possibly be added into libnode later
-  const { Worker } = require("node:worker_threads");
+  const vm = require("node:vm");
+  const { SHARE_ENV, Worker } = require("node:worker_threads");
+
+  // Serve modules added with Nodejs::add_virtual_module
+  const virtualModules = process._linkedBinding("edon:modules");
+  vm.runInThisContext(virtualModules.install)(require);
+
+  // Evaluate code with direct eval so it can access require(). When
+  // a timeout is given, the synchronous part of the evaluation runs inside
+  // a vm.Script which interrupts it once the timeout has elapsed.
+  //
+  // Workers are given this function by its source so its direct eval sees
+  // the scope of the worker, it must not contain backticks or template placeholders
+  function evaluate(code, timeout) {
+    if (timeout === undefined) {
+      return eval(code);
+    }
+    evaluate.timeoutScript ??= new vm.Script("globalThis[Symbol.for('edon.evaluate')]()");
+    globalThis[Symbol.for("edon.evaluate")] = () => eval(code);
+    try {
+      return evaluate.timeoutScript.runInThisContext({ timeout });
+    } finally {
+      delete globalThis[Symbol.for("edon.evaluate")];
+    }
+  }
 
   const cjsWorker = /*javascript*/`
+    const fs = require("node:fs");
     const module = require("node:module");
+    const path = require("node:path");
     const process = require("node:process");
+    const vm = require("node:vm");
     const { parentPort, workerData } = require("node:worker_threads");
 
+    // Workers cannot change the working directory of the process, so
+    // a custom one is reported by process.cwd() and used to resolve require()
+    if (workerData.cwd !== undefined) {
+      const cwd = workerData.cwd;
+      process.cwd = () => cwd;
+      globalThis.require = module.createRequire(path.join(cwd, "[worker eval]"));
+    }
+
+    // Serve modules added with Nodejs::add_virtual_module
+    const virtualModules = process._linkedBinding("edon:modules");
+    vm.runInThisContext(virtualModules.install)(require);
+
+    // The evaluate() of the main thread
+    ${evaluate}
+
+    // vm contexts created with NodejsWorker::create_context()
+    const contexts = {};
+    let nextContextId = 0;
+
+    function getContext(id) {
+      if (!contexts[id]) {
+        throw new Error("Context " + id + " has been destroyed");
+      }
+      return contexts[id];
+    }
+
+    // Commonjs loader for vm contexts. Modules are evaluated inside the
+    // context with a cache per context, builtin and native modules are shared with the worker
+    function createContextRequire(context, cache, parentFilename) {
+      const resolve = module.createRequire(parentFilename).resolve;
+
+      function contextRequire(specifier) {
+        if (module.isBuiltin(specifier) || specifier.startsWith("native:")) {
+          return require(specifier);
+        }
+
+        const filename = resolve(specifier);
+        if (cache[filename]) {
+          return cache[filename].exports;
+        }
+
+        const contextModule = { id: filename, filename, exports: {}, loaded: false };
+        cache[filename] = contextModule;
+
+        try {
+          const virtualSource = virtualModules.get(filename);
+          const source =
+            virtualSource === undefined ? fs.readFileSync(filename, "utf8") : String(virtualSource);
+          if (filename.endsWith(".json")) {
+            contextModule.exports = JSON.parse(source);
+          } else {
+            const wrapper = vm.compileFunction(
+              source,
+              ["exports", "require", "module", "__filename", "__dirname"],
+              { filename, parsingContext: context },
+            );
+            wrapper.call(
+              contextModule.exports,
+              contextModule.exports,
+              createContextRequire(context, cache, filename),
+              contextModule,
+              filename,
+              path.dirname(filename),
+            );
+          }
+        } catch (error) {
+          delete cache[filename];
+          throw error;
+        }
+
+        contextModule.loaded = true;
+        return contextModule.exports;
+      }
+
+      contextRequire.resolve = resolve;
+      contextRequire.cache = cache;
+      return contextRequire;
+    }
+
     let active = true;
 
     process
       ._linkedBinding("edon:worker")
-      .onEvent(workerData, async (action, payload, done) => {
+      .onEvent(workerData.tx, async (action, payload, done, reject) => {
         if (!active) {
           // TODO return error
           // This shouldn't happen though it's nice just in case
@@ -23,24 +129,125 @@ void (function () {
           switch (action) {
             // NodejsWorkerEvent::Eval
             case 0: {
-              done(eval(payload));
+              // [code, awaitPromise, timeoutMs]
+              try {
+                if (payload[1]) {
+                  done(await evaluate(payload[0], payload[2]));
+                } else {
+                  done(evaluate(payload[0], payload[2]));
+                }
+              } catch (error) {
+                reject(error);
+              }
               break;
             }
             // NodejsWorkerEvent::EvalTypeScript
             case 1: {
-              done(eval(module.stripTypeScriptTypes(payload)));
+              // [code, awaitPromise, timeoutMs]
+              try {
+                if (payload[1]) {
+                  done(await evaluate(module.stripTypeScriptTypes(payload[0]), payload[2]));
+                } else {
+                  done(evaluate(module.stripTypeScriptTypes(payload[0]), payload[2]));
+                }
+              } catch (error) {
+                reject(error);
+              }
               break;
             }
             // NodejsWorkerEvent::Require
             case 2: {
-              require(payload);
-              done();
+              try {
+                require(payload);
+                done();
+              } catch (error) {
+                reject(error);
+              }
               break;
             }
             // NodejsWorkerEvent::Import
             case 3: {
-              await import(payload);
-              done();
+              try {
+                await import(payload);
+                done();
+              } catch (error) {
+                reject(error);
+              }
+              break;
+            }
+            // NodejsWorkerEvent::CreateContext
+            case 4: {
+              // [...globals]
+              const id = String(nextContextId++);
+              const context = vm.createContext({}, { name: "edon:context:" + id });
+              const contextRequire = createContextRequire(
+                context,
+                {},
+                path.join(process.cwd(), "[edon:context:" + id + "]"),
+              );
+              for (const name of payload) {
+                context[name] = name === "require" ? contextRequire : globalThis[name];
+              }
+              contexts[id] = { context, require: contextRequire };
+              done(id);
+              break;
+            }
+            // NodejsWorkerEvent::DestroyContext
+            case 5: {
+              delete contexts[payload];
+              break;
+            }
+            // NodejsWorkerEvent::ContextEval
+            case 6: {
+              // [id, code, typescript, awaitPromise, timeoutMs]
+              const [id, code, typescript, awaitPromise, timeout] = payload;
+              try {
+                const result = vm.runInContext(
+                  typescript ? module.stripTypeScriptTypes(code) : code,
+                  getContext(id).context,
+                  { timeout, importModuleDynamically: vm.constants.USE_MAIN_CONTEXT_DEFAULT_LOADER },
+                );
+                done(awaitPromise ? await result : result);
+              } catch (error) {
+                reject(error);
+              }
+              break;
+            }
+            // NodejsWorkerEvent::ContextExec
+            case 7: {
+              try {
+                done(getContext(payload).context);
+              } catch (error) {
+                reject(error);
+              }
+              break;
+            }
+            // NodejsWorkerEvent::ContextRequire
+            case 8: {
+              // [id, specifier]
+              try {
+                getContext(payload[0]).require(payload[1]);
+                done();
+              } catch (error) {
+                reject(error);
+              }
+              break;
+            }
+            // NodejsWorkerEvent::ContextImport
+            case 9: {
+              // [id, specifier]
+              // Calls import() from inside the context, the same as eval'd code of the context does
+              try {
+                const contextImport = vm.runInContext(
+                  "(specifier) => import(specifier)",
+                  getContext(payload[0]).context,
+                  { importModuleDynamically: vm.constants.USE_MAIN_CONTEXT_DEFAULT_LOADER },
+                );
+                await contextImport(payload[1]);
+                done();
+              } catch (error) {
+                reject(error);
+              }
               break;
             }
           }
@@ -62,18 +269,24 @@ void (function () {
   // Handle requests from the host
   process
     ._linkedBinding("edon:main")
-    .onEvent(async (action, payload, done) => {
+    .onEvent(async (action, payload, done, reject) => {
       switch (action) {
         // NodejsMainEvent::StopMain
         case 0: {
-          for (const worker of Object.values(workers)) {
+          // timeoutMs
+          // Workers are stopped concurrently, those that don't answer in time
+          // (e.g. stuck in a loop) have their execution terminated by worker.terminate()
+          await Promise.all(Object.values(workers).map(async worker => {
             const onend = new Promise(res => worker.once('message', res))
             worker.postMessage(null)
-            await onend
+            let timer
+            const ontimeout = new Promise(res => { timer = setTimeout(res, payload) })
+            await Promise.race([onend, ontimeout])
+            clearTimeout(timer)
             const onclose = new Promise(res => worker.once('exit', res))
             await worker.terminate()
             await onclose
-          }
+          }))
           // Flush promises
           await new Promise(res => setTimeout(res, 0))
           done()
@@ -81,60 +294,121 @@ void (function () {
         }  
         // NodejsMainEvent::Eval
         case 1: {
-          done(eval(payload));
+          // [code, awaitPromise, timeoutMs]
+          try {
+            if (payload[1]) {
+              done(await evaluate(payload[0], payload[2]));
+            } else {
+              done(evaluate(payload[0], payload[2]));
+            }
+          } catch (error) {
+            reject(error);
+          }
           break;
         }
         // NodejsMainEvent::EvalTypeScript
         case 2: {
-          done(eval(module.stripTypeScriptTypes(payload)));
+          // [code, awaitPromise, timeoutMs]
+          try {
+            if (payload[1]) {
+              done(await evaluate(module.stripTypeScriptTypes(payload[0]), payload[2]));
+            } else {
+              done(evaluate(module.stripTypeScriptTypes(payload[0]), payload[2]));
+            }
+          } catch (error) {
+            reject(error);
+          }
           break;
         }
         // NodejsMainEvent::Require
         case 3: {
-          require(payload);
-          done();
+          try {
+            require(payload);
+            done();
+          } catch (error) {
+            reject(error);
+          }
           break;
         }
         // NodejsMainEvent::Import
         case 4: {
-          await import(payload);
-          done();
+          try {
+            await import(payload);
+            done();
+          } catch (error) {
+            reject(error);
+          }
           break;
         }
         // NodejsMainEvent::StartWorker
         case 5: {
-          const [argv, tx_worker] = payload
-
-          let worker = new Worker(cjsWorker, {
-            argv,
-            workerData: tx_worker,
-            eval: true,
-            stderr: true,
-            stdout: true,
-            stdin: false,
-          })
+          // [{ argv, execArgv, env, shareEnv, cwd, resourceLimits }, tx_worker, onStdout, onStderr, onExit]
+          const [{ shareEnv, cwd, ...options }, tx_worker, onStdout, onStderr, onExit] = payload
+
+          let worker
+          try {
+            worker = new Worker(cjsWorker, {
+              ...options,
+              ...(shareEnv ? { env: SHARE_ENV } : {}),
+              workerData: { tx: tx_worker, cwd },
+              eval: true,
+              stderr: true,
+              stdout: true,
+              stdin: false,
+            })
+          } catch (error) {
+            reject(error)
+            break
+          }
 
           worker.ref()
-          workers[worker.threadId] = worker
-          worker.stdout.on('data', d => process.stdout.write(d))
-          worker.stderr.on('data', d => process.stderr.write(d))
+          const id = worker.threadId
+          workers[id] = worker
 
-          await new Promise(res => worker.once('message', res))
-          done(`${worker.threadId}`)
+          // Report the exit code and the uncaught exception, if
+          // any, when the worker exits for whatever reason
+          let uncaught
+          worker.on('error', error => { uncaught = error })
+          worker.once('exit', code => {
+            delete workers[id]
+            onExit(code, uncaught, uncaught?.code === 'ERR_WORKER_OUT_OF_MEMORY')
+          })
+          // Output is forwarded to the host process unless
+          // the worker was given its own sink
+          worker.stdout.on('data', onStdout ?? (d => process.stdout.write(d)))
+          worker.stderr.on('data', onStderr ?? (d => process.stderr.write(d)))
+
+          // A worker that exits before it is ready has already been reported
+          await new Promise(res => {
+            worker.once('message', res)
+            worker.once('exit', res)
+          })
+          done(`${id}`)
           break
         }
         // NodejsMainEvent::StopWorker
         case 6: {
-          if (workers[payload]) {
-            const onend = new Promise(res => workers[payload].once('message', res))
-            workers[payload].postMessage(null)
-            await onend
-            await workers[payload].terminate()
-            delete workers[payload]
+          // [id, timeoutMs]
+          const [id, timeout] = payload
+          const worker = workers[id]
+          // A worker that has already exited is reported as undefined
+          if (!worker) {
+            done(undefined)
+            break
           }
-          done()
+          delete workers[id]
+          const onend = new Promise(res => worker.once('message', res))
+          worker.postMessage(null)
+          // A worker that doesn't answer in time (e.g. stuck in a loop)
+          // has its execution terminated by worker.terminate()
+          let timer
+          const ontimeout = new Promise(res => { timer = setTimeout(res, timeout, true) })
+          const forced = await Promise.race([onend.then(() => false), ontimeout])
+          clearTimeout(timer)
+          await worker.terminate()
+          done(forced)
           break
-        }        
+        }
       }
     });
 })();
@@ -1 +1,5 @@
 pub static MAIN_JS: &str = include_str!("./main.cjs");
+pub static CHANNEL_JS: &str = include_str!("./channel.cjs");
+pub static HEAP_STATISTICS_JS: &str = include_str!("./heap_statistics.cjs");
+pub static VIRTUAL_MODULES_JS: &str = include_str!("./virtual_modules.cjs");
+pub static MODULE_HOOKS_JS: &str = include_str!("./module_hooks.mjs");
@@ -0,0 +1,100 @@
+// Module customization hooks that serve native modules, modules from
+// Rust module loaders and modules added with Nodejs::add_virtual_module
+// to import()
+import path from "node:path";
+import process from "node:process";
+import { fileURLToPath, pathToFileURL } from "node:url";
+
+const modules = process._linkedBinding("edon:modules");
+
+function formatOf(filename) {
+  switch (path.extname(filename)) {
+    case ".mjs":
+      return "module";
+    case ".json":
+      return "json";
+    default:
+      return "commonjs";
+  }
+}
+
+// Native modules are imported as "native:<name>", the exports are the default
+// export and their properties are named exports. The version is part of the
+// URL so a replaced module is imported again.
+//
+// The exports are created by require() on the importing thread. Only their names
+// are read here, the module's init must not run on the hooks thread
+function nativeModuleSource(name) {
+  if (modules.nativeVersion(name) === undefined) {
+    return undefined;
+  }
+  const names = (modules.nativeExportNames(name) ?? []).filter((key) => key !== "default");
+  return [
+    `import { createRequire } from "node:module";`,
+    `const exports = createRequire(process.cwd() + "/")(${JSON.stringify("native:" + name)});`,
+    "export default exports;",
+    ...names.map((key, i) => `const e${i} = exports[${JSON.stringify(key)}]; export { e${i} as ${JSON.stringify(key)} };`),
+  ].join("\n");
+}
+
+export async function resolve(specifier, context, nextResolve) {
+  if (specifier.startsWith("native:")) {
+    const name = specifier.slice("native:".length);
+    const version = modules.nativeVersion(name);
+    if (version === undefined) {
+      const error = new Error(`Cannot find native module '${name}'`);
+      error.code = "ERR_MODULE_NOT_FOUND";
+      throw error;
+    }
+    return { url: `native:${name}?version=${version}`, format: "module", shortCircuit: true };
+  }
+
+  const url = modules.resolve(specifier, context.parentURL);
+  if (url !== undefined) {
+    return { url, shortCircuit: true };
+  }
+
+  const isPath =
+    specifier.startsWith("./") ||
+    specifier.startsWith("../") ||
+    specifier.startsWith("/") ||
+    specifier.startsWith("file:");
+
+  if (isPath) {
+    const parentURL = context.parentURL ?? pathToFileURL(process.cwd() + path.sep).href;
+    const url = new URL(specifier, parentURL);
+    if (url.protocol === "file:" && modules.has(fileURLToPath(url))) {
+      return { url: url.href, format: formatOf(url.pathname), shortCircuit: true };
+    }
+  }
+
+  return nextResolve(specifier, context);
+}
+
+export async function load(url, context, nextLoad) {
+  if (url.startsWith("native:")) {
+    const name = new URL(url).pathname;
+    const source = nativeModuleSource(name);
+    if (source === undefined) {
+      const error = new Error(`Cannot find native module '${name}'`);
+      error.code = "ERR_MODULE_NOT_FOUND";
+      throw error;
+    }
+    return { format: "module", source, shortCircuit: true };
+  }
+
+  const loaded = modules.load(url);
+  if (loaded !== undefined) {
+    return { format: loaded.format, source: loaded.source, shortCircuit: true };
+  }
+
+  if (url.startsWith("file:")) {
+    const filename = fileURLToPath(url);
+    const source = modules.get(filename);
+    if (source !== undefined) {
+      return { format: context.format ?? formatOf(filename), source, shortCircuit: true };
+    }
+  }
+
+  return nextLoad(url, context);
+}
@@ -0,0 +1,86 @@
+// Serves native modules and modules added with Nodejs::add_virtual_module to require()
+// and registers the hooks that serve them to import()
+(function installVirtualModules(require) {
+  const Module = require("node:module");
+  const path = require("node:path");
+  const virtualModules = process._linkedBinding("edon:modules");
+
+  function resolveVirtualFilename(request, parentFilename) {
+    const isPath =
+      request === "." ||
+      request === ".." ||
+      request.startsWith("./") ||
+      request.startsWith("../") ||
+      path.isAbsolute(request);
+
+    if (!isPath) {
+      return undefined;
+    }
+
+    const base = path.resolve(parentFilename ? path.dirname(parentFilename) : process.cwd(), request);
+    const candidates = [
+      base,
+      base + ".js",
+      base + ".cjs",
+      base + ".json",
+      path.join(base, "index.js"),
+      path.join(base, "index.cjs"),
+      path.join(base, "index.json"),
+    ];
+
+    return candidates.find((filename) => virtualModules.has(filename));
+  }
+
+  // Native modules registered with Nodejs::napi_module_register are
+  // required as "native:<name>". They resolve to the specifier itself,
+  // which is cached as an already loaded module holding the exports.
+  // A module that was replaced since it was cached is loaded again
+  function resolveNativeFilename(request) {
+    if (!request.startsWith("native:")) {
+      return undefined;
+    }
+    const name = request.slice("native:".length);
+    const version = virtualModules.nativeVersion(name);
+    if (version === undefined) {
+      const error = new Error(`Cannot find native module '${name}'`);
+      error.code = "MODULE_NOT_FOUND";
+      throw error;
+    }
+    if (Module._cache[request]?.nativeVersion !== version) {
+      const loaded = virtualModules.loadNative(name);
+      const nativeModule = new Module(request);
+      nativeModule.filename = request;
+      nativeModule.exports = loaded.exports;
+      nativeModule.nativeVersion = loaded.version;
+      nativeModule.loaded = true;
+      Module._cache[request] = nativeModule;
+    }
+    return request;
+  }
+
+  const resolveFilename = Module._resolveFilename;
+  Module._resolveFilename = function (request, parent, isMain, options) {
+    return (
+      resolveNativeFilename(request) ??
+      resolveVirtualFilename(request, parent?.filename) ??
+      resolveFilename.call(this, request, parent, isMain, options)
+    );
+  };
+
+  for (const extension of [".js", ".cjs", ".json"]) {
+    const loadExtension = Module._extensions[extension];
+    Module._extensions[extension] = function (module, filename) {
+      const source = virtualModules.get(filename);
+      if (source === undefined) {
+        return loadExtension.call(this, module, filename);
+      }
+      if (extension === ".json") {
+        module.exports = JSON.parse(String(source));
+      } else {
+        module._compile(String(source), filename);
+      }
+    };
+  }
+
+  Module.register("data:text/javascript," + encodeURIComponent(virtualModules.hooks));
+})
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::OutputSink;
+
+/// Options for a Nodejs worker thread
+///
+/// [Read more here](https://nodejs.org/api/worker_threads.html#new-workerfilename-options)
+#[derive(Debug, Default, Clone)]
+pub struct WorkerOptions {
+  /// Arguments appended to `process.argv` in the worker
+  pub argv: Vec<String>,
+  /// Node.js CLI options for the worker. Defaults to the options of the main thread
+  ///
+  /// Options that apply to the whole process, such as `--max-old-space-size`,
+  /// are rejected, use `resource_limits` to cap the memory of a worker
+  pub exec_argv: Option<Vec<String>>,
+  /// Environment variables of the worker
+  pub env: WorkerEnv,
+  /// Working directory of the worker, relative paths are resolved
+  /// against the host's working directory. Defaults to the host's
+  ///
+  /// Workers cannot change the working directory of the process, instead
+  /// `process.cwd()` returns this directory and `require()` resolves from it
+  pub cwd: Option<PathBuf>,
+  /// Heap limits of the worker, exceeding them stops the worker
+  /// with `Error::WorkerOutOfMemory`
+  pub resource_limits: WorkerResourceLimits,
+  /// Where the stdout of the worker is written
+  pub stdout: OutputSink,
+  /// Where the stderr of the worker is written
+  pub stderr: OutputSink,
+}
+
+/// Environment variables of a Nodejs worker thread
+#[derive(Debug, Default, Clone)]
+pub enum WorkerEnv {
+  /// A copy of the host process's environment, taken when the worker starts
+  #[default]
+  Inherit,
+  /// The environment of the host process itself, changes made by the
+  /// worker or the host are visible to both. Same as `SHARE_ENV` in Nodejs
+  Share,
+  /// Only these variables, changes made by the worker stay in the worker
+  Custom(HashMap<String, String>),
+}
+
+/// Heap limits of a worker in MiB, unset limits use the V8 defaults
+///
+/// [Read more here](https://nodejs.org/api/worker_threads.html#workerresourcelimits)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorkerResourceLimits {
+  /// The maximum size of the main heap
+  pub max_old_generation_size_mb: Option<u32>,
+  /// The maximum size of the heap space for recently created objects
+  pub max_young_generation_size_mb: Option<u32>,
+  /// The size of the pre-allocated memory range used for generated code
+  pub code_range_size_mb: Option<u32>,
+  /// The default maximum stack size of the thread
+  pub stack_size_mb: Option<u32>,
+}
@@ -0,0 +1,9 @@
+/// Loads Nodejs, panicking when libnode cannot be found
+///
+/// Tests using this are `#[ignore]`d, run them with `cargo test -- --ignored`
+/// once `EDON_LIBNODE_PATH` points at libnode
+pub fn load_nodejs() -> edon::Nodejs {
+  let libnode_path = edon::auto_resolve_libnode()
+    .expect("libnode not found, set EDON_LIBNODE_PATH to run this test");
+  edon::Nodejs::load_default(libnode_path).unwrap()
+}
@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn eval_as_unit_ignores_the_completion_value() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+  let worker = nodejs.spawn_worker_thread()?;
+
+  nodejs.eval_blocking::<()>("console.log(1); globalThis.x = 2")?;
+  worker.eval_blocking::<()>("console.log(1); globalThis.x = 2")?;
+  assert_eq!(nodejs.eval_blocking::<u32>("globalThis.x")?, 2);
+  assert_eq!(worker.eval_blocking::<u32>("globalThis.x")?, 2);
+
+  Ok(())
+}
@@ -0,0 +1,22 @@
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn exec_panic_leaves_runtime_usable() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+  let worker = nodejs.spawn_worker_thread()?;
+
+  nodejs.exec(|_env| panic!("main exec panicked"))?;
+  nodejs.exec(|_env| Err(edon::Error::generic("main exec failed")))?;
+  worker.exec(|_env| panic!("worker exec panicked"))?;
+  worker.exec(|_env| Err(edon::Error::generic("worker exec failed")))?;
+
+  let result = nodejs.exec_blocking(|_env| -> edon::Result<()> { panic!("main exec panicked") });
+  assert!(matches!(result, Err(edon::Error::Panic(_))));
+  let result = worker.exec_blocking(|_env| -> edon::Result<()> { panic!("worker exec panicked") });
+  assert!(matches!(result, Err(edon::Error::Panic(_))));
+
+  assert_eq!(nodejs.eval_blocking::<u32>("1 + 1")?, 2);
+  assert_eq!(worker.eval_blocking::<u32>("1 + 1")?, 2);
+  Ok(())
+}
@@ -0,0 +1,30 @@
+mod common;
+
+fn operation_of<T>(result: edon::Result<T>) -> Option<edon::Operation> {
+  match result {
+    Err(edon::Error::JsException { operation, .. }) => operation,
+    _ => panic!("Expected a JavaScript exception"),
+  }
+}
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn js_exceptions_record_the_operation_that_threw() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+  let worker = nodejs.spawn_worker_thread()?;
+
+  assert_eq!(
+    operation_of(nodejs.eval_blocking::<()>("throw new Error('eval')")),
+    Some(edon::Operation::Eval)
+  );
+  assert_eq!(
+    operation_of(worker.require("./does-not-exist.js")),
+    Some(edon::Operation::Require)
+  );
+  assert_eq!(
+    operation_of(worker.import("./does-not-exist.mjs")),
+    Some(edon::Operation::Import)
+  );
+
+  Ok(())
+}
@@ -0,0 +1,30 @@
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn native_modules_registered_after_load_are_served() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  // Spawned before the modules exist, it picks them up on first use
+  let worker = nodejs.spawn_worker_thread()?;
+
+  nodejs.napi_module_register("late_native_module_test", |env, mut exports| {
+    exports.set_named_property("answer", env.create_uint32(42)?)?;
+    Ok(exports)
+  })?;
+  nodejs.register_native_module(
+    edon::NativeModule::builder("late_native_module_builder_test")
+      .function("add", |a: u32, b: u32| Ok(a + b))
+      .build()?,
+  )?;
+
+  for code in [
+    r#"require("native:late_native_module_test").answer"#,
+    r#"require("native:late_native_module_builder_test").add(40, 2)"#,
+  ] {
+    assert_eq!(nodejs.eval_blocking::<u32>(code)?, 42);
+    assert_eq!(worker.eval_blocking::<u32>(code)?, 42);
+  }
+
+  Ok(())
+}
@@ -0,0 +1,25 @@
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn load_after_shutdown_returns_shut_down() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  edon::Nodejs::shutdown()?;
+  assert!(!edon::Nodejs::is_running());
+  assert!(matches!(
+    nodejs.eval_blocking::<u32>("1 + 1"),
+    Err(edon::Error::NodejsNotRunning)
+  ));
+
+  let libnode_path = edon::auto_resolve_libnode()?;
+  assert!(matches!(
+    edon::Nodejs::load_default(libnode_path),
+    Err(edon::Error::NodejsShutDown)
+  ));
+  assert!(matches!(
+    edon::Nodejs::shutdown(),
+    Err(edon::Error::NodejsNotRunning)
+  ));
+  Ok(())
+}
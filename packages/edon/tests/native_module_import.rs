@@ -0,0 +1,86 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn import_runs_native_module_init_once_per_thread() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  let inits = Arc::new(AtomicUsize::new(0));
+  nodejs.register_native_module(
+    edon::NativeModule::builder("import_init_test")
+      .function("add", |a: u32, b: u32| Ok(a + b))
+      .build()?,
+  )?;
+  nodejs.napi_module_register("import_init_count_test", {
+    let inits = inits.clone();
+    move |env, mut exports| {
+      inits.fetch_add(1, Ordering::SeqCst);
+      exports.set_named_property("answer", env.create_uint32(42)?)?;
+      Ok(exports)
+    }
+  })?;
+
+  let worker = nodejs.spawn_worker_thread()?;
+  let options = edon::EvalOptions {
+    await_promise: true,
+    ..Default::default()
+  };
+
+  // Registering creates the module once to learn its export names
+  assert_eq!(inits.load(Ordering::SeqCst), 1);
+
+  // Named exports of builder modules are known without loading them
+  let sum = worker.eval_with_options_blocking::<u32>(
+    "import('native:import_init_test').then(({ add }) => add(40, 2))",
+    &options,
+  )?;
+  assert_eq!(sum, 42);
+
+  // Named exports are available to a thread that imports the module before
+  // anything required it, and the exports are created on that thread only
+  let answer = worker.eval_with_options_blocking::<u32>(
+    "import('native:import_init_count_test').then(({ answer }) => answer)",
+    &options,
+  )?;
+  assert_eq!(answer, 42);
+  assert_eq!(inits.load(Ordering::SeqCst), 2);
+
+  let answer = nodejs.eval_with_options_blocking::<u32>(
+    "import('native:import_init_count_test').then(({ answer }) => answer)",
+    &options,
+  )?;
+  assert_eq!(answer, 42);
+  assert_eq!(inits.load(Ordering::SeqCst), 3);
+
+  Ok(())
+}
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn native_modules_registered_on_the_main_thread_have_named_exports() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  // Registering from the main thread learns the export names there
+  // rather than waiting on the main thread from itself
+  nodejs.exec_blocking(|_env| {
+    common::load_nodejs().napi_module_register("import_main_thread_test", |env, mut exports| {
+      exports.set_named_property("answer", env.create_uint32(42)?)?;
+      Ok(exports)
+    })
+  })?;
+
+  let answer = nodejs.eval_with_options_blocking::<u32>(
+    "import('native:import_main_thread_test').then(({ answer }) => answer)",
+    &edon::EvalOptions {
+      await_promise: true,
+      ..Default::default()
+    },
+  )?;
+  assert_eq!(answer, 42);
+
+  Ok(())
+}
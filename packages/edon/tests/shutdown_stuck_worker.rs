@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn shutdown_terminates_stuck_workers() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  let worker = nodejs.spawn_worker_thread()?;
+  worker.eval("while (true) {}", |_env, _result| {})?;
+
+  edon::Nodejs::shutdown_with_timeout(Duration::from_millis(100))?;
+  assert!(!edon::Nodejs::is_running());
+
+  drop(worker);
+  Ok(())
+}
@@ -0,0 +1,21 @@
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn terminating_an_exited_worker_reports_its_exit() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  let worker = nodejs.spawn_worker_thread()?;
+  let (tx, rx) = channel();
+  worker.on_exit(move |_exit| tx.send(()).unwrap());
+  worker.eval("process.exit(1)", |_env, _result| {})?;
+  rx.recv().unwrap();
+
+  let result = worker.terminate(Duration::from_secs(1));
+  assert!(matches!(result, Err(edon::Error::WorkerExited)));
+
+  Ok(())
+}
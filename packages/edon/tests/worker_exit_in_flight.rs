@@ -0,0 +1,32 @@
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn in_flight_requests_report_why_the_worker_exited() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  // The request is still waiting for its promise when the worker exits
+  let worker = nodejs.spawn_worker_thread()?;
+  let result = worker.eval_with_options_blocking::<()>(
+    "setTimeout(() => process.exit(1), 10); new Promise(() => {})",
+    &edon::EvalOptions {
+      await_promise: true,
+      ..Default::default()
+    },
+  );
+  assert!(matches!(result, Err(edon::Error::WorkerExited)));
+
+  // The request is still running when the worker runs out of memory
+  let worker = nodejs.spawn_worker_thread_with_options(&edon::WorkerOptions {
+    resource_limits: edon::WorkerResourceLimits {
+      max_old_generation_size_mb: Some(32),
+      ..Default::default()
+    },
+    ..Default::default()
+  })?;
+  let result = worker
+    .eval_blocking::<()>("const data = []; while (true) data.push(new Array(100_000).fill(0))");
+  assert!(matches!(result, Err(edon::Error::WorkerOutOfMemory)));
+
+  Ok(())
+}
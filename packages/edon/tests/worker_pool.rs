@@ -0,0 +1,56 @@
+use std::sync::mpsc::channel;
+
+mod common;
+
+/// Exit `worker` with `process.exit()` and wait until its exit is observed
+fn exit_worker(worker: &edon::NodejsWorker) {
+  let (tx, rx) = channel();
+  worker.on_exit(move |_exit| tx.send(()).unwrap());
+  worker.eval("process.exit(1)", |_env, _result| {}).unwrap();
+  rx.recv().unwrap();
+}
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn worker_pool_skips_exited_workers() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  for dispatch in [
+    edon::WorkerPoolDispatch::RoundRobin,
+    edon::WorkerPoolDispatch::LeastBusy,
+  ] {
+    let pool = nodejs.spawn_worker_pool(&edon::NodejsWorkerPoolOptions {
+      size: 3,
+      dispatch,
+      ..Default::default()
+    })?;
+    exit_worker(pool.workers().next().unwrap());
+
+    for _ in 0..6 {
+      assert_eq!(pool.eval_blocking::<u32>("1 + 1")?, 2);
+    }
+  }
+
+  Ok(())
+}
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn worker_pool_job_reports_worker_exit() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  let pool = nodejs.spawn_worker_pool(&edon::NodejsWorkerPoolOptions {
+    size: 1,
+    ..Default::default()
+  })?;
+
+  // The worker exits while running the job
+  let job = pool.eval_job::<u32>("process.exit(1)")?;
+  assert!(matches!(job.wait(), Err(edon::Error::WorkerExited)));
+
+  // Every worker has exited
+  let result = pool.eval_blocking::<u32>("1 + 1");
+  assert!(matches!(result, Err(edon::Error::WorkerExited)));
+
+  Ok(())
+}
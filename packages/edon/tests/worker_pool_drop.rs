@@ -0,0 +1,21 @@
+mod common;
+
+#[test]
+#[ignore = "requires libnode, set EDON_LIBNODE_PATH and run with --ignored"]
+fn dropping_a_pool_stops_stuck_workers_concurrently() -> edon::Result<()> {
+  let nodejs = common::load_nodejs();
+
+  let pool = nodejs.spawn_worker_pool(&edon::NodejsWorkerPoolOptions {
+    size: 3,
+    ..Default::default()
+  })?;
+  for worker in pool.workers() {
+    worker.eval("while (true) {}", |_env, _result| {})?;
+  }
+
+  let started = std::time::Instant::now();
+  drop(pool);
+  assert!(started.elapsed() < edon::DEFAULT_WORKER_STOP_TIMEOUT * 2);
+
+  Ok(())
+}